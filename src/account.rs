@@ -11,8 +11,10 @@
 * limitations under the License.
 */
 use std::sync::Arc;
-use crate::helpers::{check_dir, create_client_verbose, json_account, print_account, query_account_field};
+use crate::call::decode_call_parameters;
+use crate::helpers::{check_dir, create_client_verbose, json_account, load_abi, print_account, query_account_field};
 use crate::config::Config;
+use chrono::{Local, TimeZone};
 use serde_json::{json, Value};
 use ton_client::error::ClientError;
 use ton_client::net::{ParamsOfQueryCollection, query_collection, ResultOfSubscription, ParamsOfSubscribeCollection};
@@ -388,3 +390,79 @@ pub async fn wait_for_change(config: &Config, account_address: &str, wait_secs:
     }
     res
 }
+
+/// Subscribes to inbound messages of the account and prints them decoded with the ABI
+/// until Ctrl-C is pressed.
+pub async fn watch_account(config: &Config, account_address: &str, abi_path: &str) -> Result<(), String> {
+    let context = create_client_verbose(config)?;
+    let abi = load_abi(abi_path, config).await?;
+
+    let (s, mut r) = tokio::sync::mpsc::channel(16);
+    let callback = move |result: Result<ResultOfSubscription, ClientError>| {
+        let s = s.clone();
+        async move {
+            let res = result
+                .map(|res| res.result)
+                .map_err(|e| format!("Client error: {}", e));
+            let _ = s.send(res).await;
+        }
+    };
+
+    let subscription = ton_client::net::subscribe_collection(
+        context.clone(),
+        ParamsOfSubscribeCollection {
+            collection: "messages".to_owned(),
+            filter: Some(json!({
+                "dst": {
+                    "eq": account_address
+                },
+            })),
+            result: "id boc created_at".to_owned(),
+            ..Default::default()
+        },
+        callback
+    ).await.map_err(|e| format!("Failed to subscribe: {}", e))?;
+
+    if !config.is_json {
+        println!("Watching for inbound messages. Press Ctrl-C to stop.");
+    }
+    let res = loop {
+        let message = tokio::select! {
+            message = r.recv() => message,
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+        };
+        let message = match message {
+            Some(Ok(message)) => message,
+            Some(Err(e)) => break Err(e),
+            None => break Err("Sender has dropped".to_owned()),
+        };
+        let time = message["created_at"].as_i64()
+            .and_then(|time| Local.timestamp_opt(time, 0).single())
+            .map(|time| time.to_rfc2822())
+            .unwrap_or_else(|| "unknown".to_owned());
+        let decoded = match message["boc"].as_str() {
+            Some(boc) => decode_call_parameters(context.clone(), boc, abi.clone()).await,
+            None => Err(format!("Can't parse the result: {}", message)),
+        };
+        if !config.is_json {
+            match decoded {
+                Ok((method, params)) => println!("[{}] {}: {}", time, method, params),
+                Err(e) => println!("[{}] {}: {}", time, message["id"].as_str().unwrap_or("unknown"), e),
+            }
+        } else {
+            let decoded = match decoded {
+                Ok((method, params)) => json!({ "Method": method, "Parameters": params }),
+                Err(e) => json!({ "Error": e }),
+            };
+            println!("{:#}", json!({
+                "Time": time,
+                "MessageId": message["id"],
+                "Message": decoded,
+            }));
+        }
+    };
+
+    ton_client::net::unsubscribe(context.clone(), subscription).await
+        .map_err(|e| format!("Failed to unsubscribe: {}", e))?;
+    res
+}
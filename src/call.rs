@@ -12,161 +12,1577 @@
  */
 use crate::config::Config;
 use crate::convert;
-use crate::helpers::{TonClient, now_ms, create_client_verbose, load_abi, query_account_field,
-                     SDK_EXECUTION_ERROR_CODE, create_client, load_ton_abi, get_blockchain_config};
+use crate::metrics;
+use crate::helpers::{TonClient, now, now_ms, create_client_verbose, load_abi, query_account_field,
+                     SDK_EXECUTION_ERROR_CODE, SDK_LOW_BALANCE_ERROR_CODE, create_client, load_ton_abi, get_blockchain_config,
+                     query_with_limit, fee_blockchain_config, load_params, calc_acc_address, create_client_local, parse_ext_address};
 
-use ton_client::abi::{encode_message, decode_message, ParamsOfDecodeMessage, ParamsOfEncodeMessage,
-                      Abi};
+use ton_client::abi::{encode_message, decode_message, decode_account_data, ParamsOfDecodeMessage,
+                      ParamsOfDecodeAccountData, ParamsOfEncodeMessage, Abi, DeploySet, FunctionHeader, Signer,
+                      MessageBodyType, CallSet, ParamsOfEncodeMessageBody, encode_message_body};
 use ton_client::processing::{
+    DecodedOutput,
     ParamsOfSendMessage,
     ParamsOfWaitForTransaction,
     ParamsOfProcessMessage,
     ProcessingEvent,
+    ResultOfProcessMessage,
     wait_for_transaction,
     send_message,
 };
 use ton_client::tvm::{
     run_executor,
     ParamsOfRunExecutor,
-    AccountForExecutor
+    AccountForExecutor,
+    ExecutionOptions,
+    ResultOfRunExecutor,
+    TransactionFees,
 };
-use ton_block::{Account, Serializable, Deserializable, Message};
+use ton_block::{Account, CommonMsgInfo, CurrencyCollection, Serializable, Deserializable, Message,
+    Transaction, TransactionDescr, TrComputePhase, ComputeSkipReason};
+use ton_types::{BuilderData, IBitstring};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::{Arc, Mutex, atomic::AtomicU64};
+use std::time::Instant;
+use num_bigint::BigInt;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 use ton_abi::ParamType;
+use ton_executor::{BlockchainConfig, ExecuteParams, OrdinaryTransactionExecutor, TransactionExecutor};
 use ton_client::error::ClientError;
-use crate::debug::{execute_debug, DebugLogger};
-use crate::message::{EncodedMessage, prepare_message_params, print_encoded_message, unpack_message};
+use ton_client::utils::{convert_address, AddressStringFormat, ParamsOfConvertAddress};
+use ton_client::net::{OrderBy, ParamsOfQueryTransactionTree, ParamsOfWaitForCollection, SortDirection,
+                      query_transaction_tree};
+use crate::debug::{execute_debug, execute_debug_with_trace, DebugLogger};
+use crate::decode::msg_printer::tree_of_cells_into_base64;
+use crate::message::{prepare_message_params, print_encoded_message, unpack_json_message, unpack_message};
 
-async fn decode_call_parameters(ton: TonClient, msg: &EncodedMessage, abi: Abi) -> Result<(String, String), String> {
+pub async fn decode_call_parameters(ton: TonClient, message: &str, abi: Abi) -> Result<(String, Value), String> {
     let result = decode_message(
         ton,
         ParamsOfDecodeMessage {
             abi,
-            message: msg.message.clone(),
+            message: message.to_owned(),
             ..Default::default()
         },
     )
     .await
     .map_err(|e| format!("couldn't decode message: {}", e))?;
 
-    Ok((
-        result.name,
-        serde_json::to_string_pretty(
-            &result.value.unwrap_or(json!({}))
-        ).map_err(|e| format!("failed to serialize result: {}", e))?
-    ))
+    Ok((result.name, result.value.unwrap_or(json!({}))))
+}
+
+// describes how the value of the ABI type is packed into cells
+fn param_layout(kind: &ParamType) -> String {
+    let dictionary = "dictionary (1-bit flag and reference to the root cell)";
+    match kind {
+        ParamType::Uint(size) | ParamType::Int(size) => format!("{} bits", size),
+        ParamType::VarUint(size) | ParamType::VarInt(size) => format!("{}-bit length in bytes, then up to {} bytes",
+            usize::BITS - (size - 1).leading_zeros(), size - 1),
+        ParamType::Token => "4-bit length in bytes, then up to 15 bytes".to_string(),
+        ParamType::Bool => "1 bit".to_string(),
+        ParamType::Address => "MsgAddress (267 bits for standard address)".to_string(),
+        ParamType::Cell => "reference to the cell".to_string(),
+        ParamType::Bytes | ParamType::String => "reference to the chain of cells with data".to_string(),
+        ParamType::FixedBytes(size) => format!("{} bits", size * 8),
+        ParamType::Array(_) => format!("32-bit length, then {} of items", dictionary),
+        ParamType::FixedArray(_, size) => format!("{} of {} items", dictionary, size),
+        ParamType::Map(_, _) => dictionary.to_string(),
+        ParamType::Tuple(_) => "components one after another".to_string(),
+        ParamType::Optional(_) => "1-bit flag, then the value if present".to_string(),
+        ParamType::Ref(_) => "reference to the cell with the value".to_string(),
+        ParamType::Time => "64 bits".to_string(),
+        ParamType::Expire => "32 bits".to_string(),
+        ParamType::PublicKey => "1-bit flag, then 256 bits if present".to_string(),
+        _ => kind.to_string(),
+    }
+}
+
+fn annotate_param(kind: &ParamType, value: &Value) -> Value {
+    let value = match (kind, value) {
+        (ParamType::Tuple(components), _) => annotate_params(value, components),
+        (ParamType::Array(item) | ParamType::FixedArray(item, _), Value::Array(items)) =>
+            json!(items.iter().map(|item_value| annotate_param(item, item_value)).collect::<Vec<_>>()),
+        _ => value.clone(),
+    };
+    json!({
+        "type": kind.to_string(),
+        "layout": param_layout(kind),
+        "value": value,
+    })
+}
+
+/// Annotates each decoded parameter with its ABI type and cell layout. Tuple components and array
+/// items are annotated recursively.
+pub fn annotate_params(values: &Value, params: &[ton_abi::Param]) -> Value {
+    let mut result = serde_json::Map::new();
+    for param in params {
+        let value = values.get(&param.name).cloned().unwrap_or(Value::Null);
+        result.insert(param.name.clone(), annotate_param(&param.kind, &value));
+    }
+    Value::Object(result)
+}
+
+// annotates parameters if `decode_layout` config option is enabled
+async fn printable_parameters(parameters: &Value, abi_path: &str, method: &str, config: &Config) -> Result<Value, String> {
+    if !config.decode_layout {
+        return Ok(parameters.clone());
+    }
+    let abi = load_ton_abi(abi_path, config).await?;
+    let function = find_function(&abi, method)?;
+    Ok(annotate_params(parameters, function.input_params().as_slice()))
 }
 
-fn parse_integer_param(value: &str) -> Result<String, String> {
+fn parse_integer_param(value: &str, decimals: usize) -> Result<String, String> {
     let value = value.trim_matches('\"');
 
     if value.ends_with('T') {
-        convert::convert_token(value.trim_end_matches('T'))
+        let tokens = value.trim_end_matches('T');
+        let tokens = expand_scientific(tokens)?.unwrap_or(tokens.to_owned());
+        convert::convert_amount(&tokens, decimals)
+    } else {
+        match expand_scientific(value)? {
+            Some(expanded) if expanded.contains('.') =>
+                Err(format!("{} is not an integer (expands to {})", value, expanded)),
+            Some(expanded) => Ok(expanded),
+            None => Ok(value.to_owned()),
+        }
+    }
+}
+
+// Exponent limit keeps expanded values reasonable, integer ABI types don't exceed 256 bits anyway.
+const MAX_SCIENTIFIC_EXPONENT: i64 = 100;
+
+// Expands number in scientific notation (e.g. `1.5e18`) to the exact decimal representation.
+// Returns None if the value is not in scientific notation.
+fn expand_scientific(value: &str) -> Result<Option<String>, String> {
+    let (sign, unsigned) = match value.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", value),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(|c| c == 'e' || c == 'E') {
+        Some(parts) => parts,
+        None => return Ok(None),
+    };
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if int_part.is_empty() || !is_digits(int_part) || !is_digits(frac_part) {
+        return Ok(None);
+    }
+    let exponent = exponent.strip_prefix('+').unwrap_or(exponent).parse::<i64>()
+        .map_err(|e| format!("invalid exponent in {}: {}", value, e))?;
+    if exponent.abs() > MAX_SCIENTIFIC_EXPONENT {
+        return Err(format!("exponent of {} is too large", value));
+    }
+
+    let digits = format!("{}{}", int_part, frac_part);
+    let point = int_part.len() as i64 + exponent;
+    let (int_digits, frac_digits) = if point <= 0 {
+        ("0".to_string(), format!("{}{}", "0".repeat((-point) as usize), digits))
+    } else if point as usize >= digits.len() {
+        (format!("{}{}", digits, "0".repeat(point as usize - digits.len())), String::new())
     } else {
-        Ok(value.to_owned())
+        let (int_digits, frac_digits) = digits.split_at(point as usize);
+        (int_digits.to_string(), frac_digits.to_string())
+    };
+    let int_digits = match int_digits.trim_start_matches('0') {
+        "" => "0",
+        int_digits => int_digits,
+    };
+    let frac_digits = frac_digits.trim_end_matches('0');
+    let sign = if int_digits == "0" && frac_digits.is_empty() { "" } else { sign };
+    Ok(Some(if frac_digits.is_empty() {
+        format!("{}{}", sign, int_digits)
+    } else {
+        format!("{}{}.{}", sign, int_digits, frac_digits)
+    }))
+}
+
+// Suffix of the argument name which marks integer argument as a duration, e.g. `--period:duration 2h`.
+const DURATION_HINT: &str = ":duration";
+
+// parses duration with optional `s`, `m`, `h` or `d` suffix to seconds
+fn parse_duration_param(value: &str) -> Result<String, String> {
+    let value = value.trim_matches('\"');
+    let (number, multiplier) = match value.char_indices().last() {
+        Some((i, 's')) => (&value[..i], 1),
+        Some((i, 'm')) => (&value[..i], 60),
+        Some((i, 'h')) => (&value[..i], 60 * 60),
+        Some((i, 'd')) => (&value[..i], 24 * 60 * 60),
+        _ => (value, 1),
+    };
+    u64::from_str(number)
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .map(|seconds| seconds.to_string())
+        .ok_or_else(|| format!(r#"failed to parse duration "{}": expected integer with optional s, m, h or d suffix"#, value))
+}
+
+#[derive(Serialize)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub id: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub is_getter: bool,
+}
+
+/// Lists functions of the contract ABI with their ids (selectors), sorted by name.
+/// Functions without inputs that return values are treated as getters.
+pub fn list_functions(abi: &ton_abi::Contract) -> Vec<FunctionInfo> {
+    let format_params = |params: &Vec<ton_abi::Param>| params.iter()
+        .map(|param| format!("{}: {}", param.name, param.kind))
+        .collect::<Vec<_>>();
+    let mut functions = abi.functions().values()
+        .map(|function| FunctionInfo {
+            name: function.name.clone(),
+            id: format!("0x{:08x}", function.get_input_id()),
+            inputs: format_params(&function.inputs),
+            outputs: format_params(&function.outputs),
+            is_getter: function.inputs.is_empty() && !function.outputs.is_empty(),
+        })
+        .collect::<Vec<_>>();
+    functions.sort_by(|a, b| a.name.cmp(&b.name));
+    functions
+}
+
+fn parse_array_param(name: &str, value: &str, item_type: &ParamType, config: &Config) -> Result<Vec<String>, String> {
+    let mut result_vec: Vec<String> = vec![];
+    for i in value.split(|c| c == ',' || c == '[' || c == ']') {
+        let i = i.trim();
+        if !i.is_empty() {
+            let item = parse_integer_param(i, config.token_decimals as usize)?;
+            if config.check_int_range || matches!(item_type, ParamType::VarUint(_) | ParamType::VarInt(_)) {
+                check_integer_range(name, &item, item_type)?;
+            }
+            result_vec.push(item)
+        }
+    }
+    Ok(result_vec)
+}
+
+/// Converts signed integer outputs (`Int(n)`/`VarInt(n)`), which are represented as unsigned
+/// numbers (e.g. `0xff..ff` instead of `-1`), to signed decimal strings according to the ABI.
+/// Positive values are left as returned by the SDK.
+pub fn display_signed_outputs(output: Value, params: &[ton_abi::Param]) -> Value {
+    match output {
+        Value::Object(mut object) => {
+            for param in params {
+                if let Some(value) = object.get_mut(&param.name) {
+                    *value = display_signed_value(value, &param.kind);
+                }
+            }
+            Value::Object(object)
+        },
+        output => output,
+    }
+}
+
+/// Converts address fields of the decoded output to the format set by `address_format` config option.
+/// Output is returned as is if the option is "None" or an address can't be converted.
+pub fn normalize_output_addresses(output: Value, params: &[ton_abi::Param], config: &Config) -> Value {
+    let format = match config.address_format.as_str() {
+        "Raw" => AddressStringFormat::AccountId,
+        "Base64" => AddressStringFormat::Base64 { url: true, bounce: true, test: false },
+        _ => return output,
+    };
+    let ton = match create_client_local() {
+        Ok(ton) => ton,
+        Err(_) => return output,
+    };
+    normalize_addresses(ton, output, params, &format)
+}
+
+fn normalize_addresses(ton: TonClient, output: Value, params: &[ton_abi::Param], format: &AddressStringFormat) -> Value {
+    match output {
+        Value::Object(mut object) => {
+            for param in params {
+                if let Some(value) = object.get_mut(&param.name) {
+                    *value = normalize_address_value(ton.clone(), value, &param.kind, format);
+                }
+            }
+            Value::Object(object)
+        },
+        output => output,
+    }
+}
+
+fn normalize_address_value(ton: TonClient, value: &Value, kind: &ParamType, format: &AddressStringFormat) -> Value {
+    match kind {
+        ParamType::Address => match value.as_str() {
+            Some(address) if !address.is_empty() => convert_address(
+                ton,
+                ParamsOfConvertAddress {
+                    address: address.to_owned(),
+                    output_format: format.clone(),
+                },
+            )
+                .map(|result| json!(convert_raw_address(&result.address, address, format)))
+                .unwrap_or_else(|_| value.clone()),
+            _ => value.clone(),
+        },
+        ParamType::Tuple(params) => normalize_addresses(ton, value.clone(), params, format),
+        ParamType::Optional(kind) | ParamType::Ref(kind) => normalize_address_value(ton, value, kind, format),
+        ParamType::Array(item_type) | ParamType::FixedArray(item_type, _) => match value.as_array() {
+            Some(items) => json!(items.iter()
+                .map(|item| normalize_address_value(ton.clone(), item, item_type, format))
+                .collect::<Vec<_>>()),
+            None => value.clone(),
+        },
+        ParamType::Map(_, value_type) => match value.as_object() {
+            Some(items) => json!(items.iter()
+                .map(|(key, item)| (key.clone(), normalize_address_value(ton.clone(), item, value_type, format)))
+                .collect::<serde_json::Map<_, _>>()),
+            None => value.clone(),
+        },
+        _ => value.clone(),
+    }
+}
+
+// `AccountId` format drops the workchain, so it is restored from the original address.
+fn convert_raw_address(converted: &str, original: &str, format: &AddressStringFormat) -> String {
+    match format {
+        AddressStringFormat::AccountId => {
+            let workchain = ton_block::MsgAddressInt::from_str(original)
+                .map(|address| address.workchain_id())
+                .unwrap_or(0);
+            format!("{}:{}", workchain, converted)
+        },
+        _ => converted.to_owned(),
+    }
+}
+
+fn display_signed_value(value: &Value, kind: &ParamType) -> Value {
+    let width = match kind {
+        ParamType::Int(width) => *width,
+        ParamType::VarInt(size) if *size > 1 => (*size - 1) * 8,
+        ParamType::Tuple(params) => return display_signed_outputs(value.clone(), params),
+        ParamType::Array(item_type) | ParamType::FixedArray(item_type, _) => {
+            return match value.as_array() {
+                Some(items) => json!(items.iter()
+                    .map(|item| display_signed_value(item, item_type))
+                    .collect::<Vec<_>>()),
+                None => value.clone(),
+            };
+        },
+        _ => return value.clone(),
+    };
+    let string = match value.as_str() {
+        Some(string) => string,
+        None => return value.clone(),
+    };
+    let number = match string.strip_prefix("0x") {
+        Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+        None => BigInt::parse_bytes(string.as_bytes(), 10),
+    };
+    match number {
+        Some(number) if number >= BigInt::from(1) << (width - 1) && number < BigInt::from(1) << width => {
+            json!((number - (BigInt::from(1) << width)).to_string())
+        },
+        _ => value.clone(),
+    }
+}
+
+/// Checks that integer argument fits the width of `Uint(n)`/`Int(n)` type declared in the ABI.
+/// For `VarUint(n)`/`VarInt(n)` the value must fit into `n - 1` bytes, so `n` less than 2 is not supported.
+/// Other types are not checked.
+fn check_integer_range(name: &str, value: &str, kind: &ParamType) -> Result<(), String> {
+    let (signed, width) = match kind {
+        ParamType::VarUint(size) | ParamType::VarInt(size) if *size < 2 => {
+            return Err(format!(r#"argument "{}" has unsupported type "{}""#, name, kind));
+        },
+        ParamType::Uint(width) => (false, *width),
+        ParamType::Int(width) => (true, *width),
+        ParamType::VarUint(size) => (false, (*size - 1) * 8),
+        ParamType::VarInt(size) => (true, (*size - 1) * 8),
+        _ => return Ok(()),
+    };
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let number = match digits.strip_prefix("0x") {
+        Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+        None => BigInt::parse_bytes(digits.as_bytes(), 10),
+    }.ok_or(format!(r#"argument "{}" of type "{}" is not a valid integer: {}"#, name, kind, value))?;
+    let number = if negative { -number } else { number };
+    let (min, max) = if signed {
+        let half = BigInt::from(1) << (width - 1);
+        (-half.clone(), half - 1)
+    } else {
+        (BigInt::from(0), (BigInt::from(1) << width) - 1)
+    };
+    if number < min || number > max {
+        return Err(format!(r#"argument "{}" value {} is out of range of declared type "{}" ({}..{})"#,
+            name, value, kind, min, max));
+    }
+    Ok(())
+}
+
+fn check_array_item(item: &Value, kind: &ParamType, decimals: usize) -> Result<Value, String> {
+    match kind {
+        ParamType::Uint(_) | ParamType::Int(_) | ParamType::VarUint(_) | ParamType::VarInt(_) => match item {
+            Value::Number(number) => Ok(json!(number.to_string())),
+            Value::String(string) => Ok(json!(parse_integer_param(string, decimals)?)),
+            _ => Err(format!("expected integer, found {}", item)),
+        },
+        ParamType::Bool => item.as_bool()
+            .map(|_| item.clone())
+            .ok_or(format!("expected bool, found {}", item)),
+        ParamType::Address => {
+            let address = item.as_str()
+                .ok_or(format!("expected address string, found {}", item))?;
+            ton_block::MsgAddressInt::from_str(address)
+                .map_err(|e| format!("invalid address {}: {}", address, e))?;
+            Ok(item.clone())
+        },
+        ParamType::Tuple(params) => {
+            let object = item.as_object()
+                .ok_or(format!("expected tuple object, found {}", item))?;
+            let mut result = json!({});
+            for param in params {
+                let value = object.get(&param.name)
+                    .ok_or(format!(r#"tuple field "{}" not found"#, param.name))?;
+                result[param.name.clone()] = check_array_item(value, &param.kind, decimals)
+                    .map_err(|e| format!(r#"tuple field "{}": {}"#, param.name, e))?;
+            }
+            Ok(result)
+        },
+        ParamType::Array(item_type) => {
+            let array = item.as_array()
+                .ok_or(format!("expected array, found {}", item))?;
+            array.iter()
+                .map(|item| check_array_item(item, item_type, decimals))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|array| json!(array))
+        },
+        _ => Ok(item.clone()),
+    }
+}
+
+/// Loads array parameter from the json file and checks its elements against the array item type.
+fn load_array_param(name: &str, path: &str, item_type: &ParamType, decimals: usize) -> Result<Value, String> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| format!(r#"failed to read array argument "{}" from file {}: {}"#, name, path, e))?;
+    let array: Value = serde_json::from_str(&data)
+        .map_err(|e| format!(r#"failed to parse array argument "{}" from file {}: {}"#, name, path, e))?;
+    let array = array.as_array()
+        .ok_or(format!(r#"array argument "{}": file {} doesn't contain a json array"#, name, path))?;
+    let mut result = vec![];
+    for (i, item) in array.iter().enumerate() {
+        result.push(check_array_item(item, item_type, decimals)
+            .map_err(|e| format!(r#"array argument "{}", element {}: {}"#, name, i, e))?);
     }
+    Ok(json!(result))
+}
+
+/// Resolves `0x`-prefixed function id (selector) to the name of the ABI function with such input id
+/// and `#N` token to the name of the N-th (1-based) function in the `list_functions` order.
+/// Other method names are returned as is.
+pub fn resolve_function_name(abi: &ton_abi::Contract, method: &str) -> Result<String, String> {
+    if let Some(index) = method.strip_prefix('#') {
+        let index = usize::from_str(index)
+            .map_err(|e| format!(r#"failed to parse function index "{}": {}"#, method, e))?;
+        let functions = list_functions(abi);
+        return index.checked_sub(1)
+            .and_then(|i| functions.get(i))
+            .map(|function| function.name.clone())
+            .ok_or_else(|| format!("function index {} is out of range, abi has {} functions", index, functions.len()));
+    }
+    let id = match method.strip_prefix("0x").or_else(|| method.strip_prefix("0X")) {
+        Some(id) => u32::from_str_radix(id, 16)
+            .map_err(|e| format!(r#"failed to parse function id "{}": {}"#, method, e))?,
+        None => return Ok(method.to_owned()),
+    };
+    abi.functions().values()
+        .find(|function| function.get_input_id() == id)
+        .map(|function| function.name.clone())
+        .ok_or_else(|| format!("function with id 0x{:08x} not found in abi", id))
+}
+
+pub async fn resolve_method(abi_path: &str, method: &str, config: &Config) -> Result<String, String> {
+    if !method.starts_with("0x") && !method.starts_with("0X") && !method.starts_with('#') {
+        return Ok(method.to_owned());
+    }
+    resolve_function_name(&load_ton_abi(abi_path, config).await?, method)
+}
+
+fn find_function<'a>(abi: &'a ton_abi::Contract, method: &str) -> Result<&'a ton_abi::Function, String> {
+    let method = &resolve_function_name(abi, method)?;
+    let functions = abi.functions();
+    if functions.is_empty() {
+        return Err(format!(r#"failed to load function "{}" from abi: abi has no functions"#, method));
+    }
+    functions.get(method).ok_or_else(|| {
+        let mut names = functions.keys().map(|name| name.as_str()).collect::<Vec<_>>();
+        names.sort_unstable();
+        format!(r#"failed to load function "{}" from abi: function not found among {} functions ({})"#,
+            method, names.len(), names.join(", "))
+    })
+}
+
+// Looks for the argument specified either as `--name value` or as `name=value`.
+// Returns None if the argument is not found and Some(None) if it is found, but has no value.
+fn find_param_value(params_vec: &[&str], name: &str) -> Option<Option<String>> {
+    find_param_values(params_vec, name).into_iter().next()
+}
+
+// returns values of all occurrences of the parameter in the order they are specified
+fn find_param_values(params_vec: &[&str], name: &str) -> Vec<Option<String>> {
+    let mut result = vec![];
+    let mut iter = params_vec.iter();
+    while let Some(param) = iter.next() {
+        if param.starts_with('-') {
+            let value = iter.next();
+            if param.trim_start_matches('-') == name {
+                result.push(value.map(|v| v.to_string()));
+            }
+        } else if is_name_value_param(param) {
+            let (key, value) = param.split_once('=').unwrap();
+            if key == name {
+                result.push(Some(value.to_owned()));
+            }
+        }
+    }
+    result
+}
+
+// fails if the argument is specified more than once (including the duration hinted form), because
+// only the first value would be used and the rest silently ignored.
+fn check_duplicate_param(params_vec: &[&str], name: &str) -> Result<(), String> {
+    let values = find_param_values(params_vec, name).into_iter()
+        .chain(find_param_values(params_vec, &format!("{}{}", name, DURATION_HINT)))
+        .map(|value| value.unwrap_or_default())
+        .collect::<Vec<_>>();
+    if values.len() > 1 {
+        return Err(format!(r#"argument "{}" is specified {} times (values: {}), only one value is allowed"#,
+            name, values.len(), values.join(", ")));
+    }
+    Ok(())
+}
+
+// checks that parameter is specified as `name=value` token
+fn is_name_value_param(param: &str) -> bool {
+    match param.split_once('=') {
+        Some((key, _)) => {
+            let key = key.strip_suffix(DURATION_HINT).unwrap_or(key);
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        },
+        None => false,
+    }
+}
+
+// Responsible functions take the function id of the callback as their first parameter.
+const ANSWER_ID_PARAM_NAMES: [&str; 3] = ["answerId", "_answer_id", "answer_id"];
+const DEFAULT_ANSWER_ID: u32 = 0;
+
+/// Returns the name of the answer id parameter if the function is a responsible one.
+fn answer_id_param(function: &ton_abi::Function) -> Option<&str> {
+    function.input_params().first()
+        .filter(|param| param.kind == ParamType::Uint(32) && ANSWER_ID_PARAM_NAMES.contains(&param.name.as_str()))
+        .filter(|_| !function.output_params().is_empty())
+        .map(|param| param.name.as_str())
 }
 
 async fn build_json_from_params(params_vec: Vec<&str>, abi_path: &str, method: &str, config: &Config) -> Result<String, String> {
-    let abi_obj = load_ton_abi(abi_path, config).await?;
-    let functions = abi_obj.functions();
+    let (params_json, _) = build_typed_params(params_vec, abi_path, method, config).await?;
+    serde_json::to_string(&params_json).map_err(|e| format!("{}", e))
+}
 
-    let func_obj = functions.get(method).ok_or("failed to load function from abi")?;
+/// Builds function arguments from `--name value` pairs like `build_json_from_params`, but returns them as
+/// a map together with the names and ABI types of the function inputs in the order of declaration.
+pub async fn build_typed_params(
+    params_vec: Vec<&str>,
+    abi_path: &str,
+    method: &str,
+    config: &Config,
+) -> Result<(serde_json::Map<String, Value>, Vec<(String, ParamType)>), String> {
+    let abi_obj = load_ton_abi(abi_path, config).await?;
+    let func_obj = find_function(&abi_obj, method)?;
     let inputs = func_obj.input_params();
+    // function without inputs accepts no `--name value` arguments
+    if inputs.is_empty() && !params_vec.is_empty() {
+        return Err(format!(r#"function "{}" has no inputs, but arguments are specified: {}"#, method, params_vec.join(" ")));
+    }
+    let types = inputs.iter()
+        .map(|input| (input.name.clone(), input.kind.clone()))
+        .collect();
 
-    let mut params_json = json!({ });
+    let answer_id = answer_id_param(func_obj);
+    let mut params_json = serde_json::Map::new();
     for input in inputs {
-        let mut iter = params_vec.iter();
-        let _param = iter.find(|x| x.starts_with('-') && (x.trim_start_matches('-') == input.name))
-            .ok_or(format!(r#"argument "{}" of type "{}" not found"#, input.name, input.kind))?;
+        check_duplicate_param(&params_vec, &input.name)?;
+        let duration = find_param_value(&params_vec, &format!("{}{}", input.name, DURATION_HINT));
+        let is_duration = duration.is_some();
+        let value = duration.or_else(|| find_param_value(&params_vec, &input.name))
+            .or_else(|| match answer_id {
+                Some(name) if name == input.name => {
+                    if !config.is_json {
+                        println!(r#"Warning: "{}" argument of the responsible function "{}" is not specified, {} is used."#,
+                            input.name, method, DEFAULT_ANSWER_ID);
+                    }
+                    Some(Some(DEFAULT_ANSWER_ID.to_string()))
+                },
+                _ => None,
+            })
+            .or_else(|| match input.kind {
+                // absent optional argument is encoded as null
+                ParamType::Optional(_) => Some(Some("null".to_string())),
+                _ => None,
+            })
+            .ok_or(format!(r#"argument "{}" of type "{}" not found"#, input.name, input.kind))?
+            .or_else(|| match input.kind {
+                ParamType::Optional(_) => Some("null".to_string()),
+                _ => None,
+            })
+            .ok_or(format!(r#"argument "{}" of type "{}" has no value"#, input.name, input.kind))?;
 
-        let value = iter.next()
-            .ok_or(format!(r#"argument "{}" of type "{}" has no value"#, input.name, input.kind))?
-            .to_string();
+        let value = parse_typed_param(&input.name, &input.kind, &value, is_duration, config)?;
+        params_json.insert(input.name.clone(), value);
+    }
 
-        let value = match input.kind {
-            ParamType::Uint(_) | ParamType::Int(_) => {
-                json!(parse_integer_param(&value)?)
-            },
-            ParamType::Array(ref _x) => {
-                let mut result_vec: Vec<String> = vec![];
-                for i in value.split(|c| c == ',' || c == '[' || c == ']') {
-                    if !i.is_empty() {
-                        result_vec.push(parse_integer_param(i)?)
-                    }
-                }
-                json!(result_vec)
-            },
-            _ => {
-                json!(value)
+    Ok((params_json, types))
+}
+
+// Converts the argument specified as a string to the json value which the encoder expects for its ABI type.
+// Optional argument is null if it is empty or "null", otherwise it is converted as its inner type.
+fn parse_typed_param(name: &str, kind: &ParamType, value: &str, is_duration: bool, config: &Config) -> Result<Value, String> {
+    Ok(match *kind {
+        ParamType::Optional(_) if value.is_empty() || value == "null" => Value::Null,
+        ParamType::Optional(ref inner) => parse_typed_param(name, inner, value, is_duration, config)?,
+        ParamType::Uint(_) | ParamType::Int(_) | ParamType::VarUint(_) | ParamType::VarInt(_) if is_duration => {
+            let value = parse_duration_param(value)?;
+            check_integer_range(name, &value, kind)?;
+            json!(value)
+        },
+        _ if is_duration => {
+            return Err(format!(r#"argument "{}" of type "{}" can't be specified as a duration"#, name, kind));
+        },
+        ParamType::Uint(_) | ParamType::Int(_) => {
+            let value = parse_integer_param(value, config.token_decimals as usize)?;
+            if config.check_int_range {
+                check_integer_range(name, &value, kind)?;
             }
+            json!(value)
+        },
+        ParamType::VarUint(_) | ParamType::VarInt(_) => {
+            let value = parse_integer_param(value, config.token_decimals as usize)?;
+            // variable length integers are always checked, because there is no
+            // legacy behavior of passing them as is
+            check_integer_range(name, &value, kind)?;
+            json!(value)
+        },
+        ParamType::Array(ref item_type) if value.starts_with('@') => {
+            load_array_param(name, value.trim_start_matches('@'), item_type, config.token_decimals as usize)?
+        },
+        ParamType::Array(ref item_type) => {
+            let result_vec = parse_array_param(name, value, item_type, config)?;
+            json!(result_vec)
+        },
+        ParamType::FixedArray(ref item_type, size) => {
+            let result_vec = parse_array_param(name, value, item_type, config)?;
+            if result_vec.len() != size {
+                return Err(format!(r#"argument "{}" of type "{}" must contain {} elements, but {} elements are specified"#,
+                    name, kind, size, result_vec.len()));
+            }
+            json!(result_vec)
+        },
+        ParamType::FixedBytes(size) => {
+            let bytes = value.trim_matches('\"').trim_start_matches("0x").to_lowercase();
+            let len = hex::decode(&bytes)
+                .map_err(|e| format!(r#"argument "{}" of type "{}" must be a hex string: {}"#, name, kind, e))?
+                .len();
+            if len != size {
+                return Err(format!(r#"argument "{}" of type "{}" must be {} bytes long, but {} bytes are specified"#,
+                    name, kind, size, len));
+            }
+            json!(bytes)
+        },
+        _ => {
+            json!(value)
+        }
+    })
+}
+
+// Returns the name of the batch function parameter which takes bodies of the calls.
+fn batch_param(function: &ton_abi::Function) -> Result<String, String> {
+    match function.input_params().as_slice() {
+        [param] if param.kind == ParamType::Array(Box::new(ParamType::Cell)) => Ok(param.name.clone()),
+        _ => Err(format!(r#"function "{}" can't be used for batch call, it must take a single "cell[]" argument"#, function.name)),
+    }
+}
+
+/// Builds arguments of the batch function from the list of calls. The batch function is expected to take
+/// a single `cell[]` argument, each item of which is an internal message body of the call encoded with
+/// the same ABI. Calls are specified as json array of `{"method": "<name>", "params": {...}}` objects.
+pub async fn build_batch_params(calls: &str, abi_path: &str, method: &str, config: &Config) -> Result<String, String> {
+    let ton_abi = load_ton_abi(abi_path, config).await?;
+    let param = batch_param(find_function(&ton_abi, method)?)?;
+    let calls: Vec<Value> = serde_json::from_str(calls)
+        .map_err(|e| format!("batch calls are not a json array: {}", e))?;
+    if calls.is_empty() {
+        return Err("batch doesn't contain any calls".to_string());
+    }
+
+    let ton = create_client_local()?;
+    let abi = load_abi(abi_path, config).await?;
+    let mut bodies = vec![];
+    for (i, call) in calls.iter().enumerate() {
+        let name = call["method"].as_str()
+            .ok_or(format!(r#"call #{} of the batch has no "method" field"#, i + 1))?;
+        let name = resolve_function_name(&ton_abi, name)?;
+        let input = match call.get("params") {
+            Some(Value::String(params)) => serde_json::from_str(params)
+                .map_err(|e| format!("arguments of call #{} are not in json format: {}", i + 1, e))?,
+            Some(params) => params.clone(),
+            None => json!({}),
         };
-        params_json[input.name.clone()] = value;
+        let body = encode_message_body(
+            ton.clone(),
+            ParamsOfEncodeMessageBody {
+                abi: abi.clone(),
+                call_set: CallSet::some_with_function_and_input(&name, input)
+                    .ok_or(format!("failed to create CallSet for call #{} of the batch", i + 1))?,
+                is_internal: true,
+                ..Default::default()
+            },
+        ).await
+            .map_err(|e| format!("failed to encode body of call #{} of the batch: {}", i + 1, e))?;
+        bodies.push(body.body);
     }
+    serde_json::to_string(&json!({ param: bodies })).map_err(|e| format!("{}", e))
+}
+
+// Name of the function parameter which is used by wallet contracts (e.g. multisig `submitTransaction`
+// and `sendTransaction`) to specify amount of nanotons attached to the outgoing internal message.
+const VALUE_PARAM_NAME: &str = "value";
+
+pub async fn attach_value(params: &str, value: &str, abi_path: &str, method: &str, config: &Config) -> Result<String, String> {
+    let abi_obj = load_ton_abi(abi_path, config).await?;
+    let func_obj = find_function(&abi_obj, method)?;
+    let input = func_obj.input_params().iter()
+        .find(|param| param.name == VALUE_PARAM_NAME)
+        .ok_or(format!(r#"function "{}" has no "{}" parameter, value can't be attached"#, method, VALUE_PARAM_NAME))?;
+    match input.kind {
+        ParamType::Uint(_) | ParamType::VarUint(_) | ParamType::Token => {},
+        _ => return Err(format!(r#"parameter "{}" of type "{}" can't be used to attach value"#, input.name, input.kind)),
+    }
+
+    let mut params_json: Value = serde_json::from_str(params)
+        .map_err(|e| format!("arguments are not in json format: {}", e))?;
+    if !params_json.is_object() {
+        return Err("arguments are not a json object".to_string());
+    }
+    if params_json.get(VALUE_PARAM_NAME).is_some() {
+        return Err(format!(r#"parameter "{}" is specified both in arguments and with --value option"#, VALUE_PARAM_NAME));
+    }
+    params_json[VALUE_PARAM_NAME] = json!(convert::convert_token(value)?);
 
     serde_json::to_string(&params_json).map_err(|e| format!("{}", e))
 }
 
+// Name of the function parameter which is used by wallet contracts to specify body of the outgoing internal message.
+const PAYLOAD_PARAM_NAME: &str = "payload";
+// Comment must fit in a single cell after the 32-bit zero tag.
+const MAX_COMMENT_BYTES: usize = (1023 - 32) / 8;
+
+/// Builds the standard text comment cell (32-bit zero tag followed by UTF-8 text) and returns it as base64 boc.
+pub fn encode_comment(text: &str) -> Result<String, String> {
+    let bytes = text.as_bytes();
+    if bytes.len() > MAX_COMMENT_BYTES {
+        return Err(format!("comment is too long: {} bytes, maximum is {} bytes", bytes.len(), MAX_COMMENT_BYTES));
+    }
+    let mut builder = BuilderData::default();
+    builder.append_u32(0)
+        .and_then(|builder| builder.append_raw(bytes, bytes.len() * 8))
+        .map_err(|e| format!("failed to write comment to the cell: {}", e))?;
+    let cell = builder.into_cell()
+        .map_err(|e| format!("failed to build comment cell: {}", e))?;
+    Ok(base64::encode(
+        &ton_types::cells_serialization::serialize_toc(&cell)
+            .map_err(|e| format!("failed to serialize comment cell: {}", e))?
+    ))
+}
+
+pub async fn attach_comment(params: &str, comment: &str, abi_path: &str, method: &str, config: &Config) -> Result<String, String> {
+    let abi_obj = load_ton_abi(abi_path, config).await?;
+    let func_obj = find_function(&abi_obj, method)?;
+    let input = func_obj.input_params().iter()
+        .find(|param| param.name == PAYLOAD_PARAM_NAME)
+        .ok_or(format!(r#"function "{}" has no "{}" parameter, comment can't be attached"#, method, PAYLOAD_PARAM_NAME))?;
+    if input.kind != ParamType::Cell {
+        return Err(format!(r#"parameter "{}" of type "{}" can't be used to attach comment"#, input.name, input.kind));
+    }
+
+    let mut params_json: Value = serde_json::from_str(params)
+        .map_err(|e| format!("arguments are not in json format: {}", e))?;
+    if !params_json.is_object() {
+        return Err("arguments are not a json object".to_string());
+    }
+    let payload = params_json.get(PAYLOAD_PARAM_NAME).and_then(|payload| payload.as_str()).unwrap_or_default();
+    if !payload.is_empty() {
+        return Err(format!(r#"parameter "{}" is specified both in arguments and with --comment option"#, PAYLOAD_PARAM_NAME));
+    }
+    params_json[PAYLOAD_PARAM_NAME] = json!(encode_comment(comment)?);
+
+    serde_json::to_string(&params_json).map_err(|e| format!("{}", e))
+}
+
+/// Parses header override JSON (`{"time": .., "expire": .., "pubkey": ..}`).
+/// Fields absent from the JSON are left empty, so they are computed as usual on encoding.
+pub fn parse_header_override(header: &str) -> Result<FunctionHeader, String> {
+    let header: Value = serde_json::from_str(header)
+        .map_err(|e| format!("failed to parse header override: {}", e))?;
+    let header = header.as_object()
+        .ok_or("header override must be a JSON object".to_string())?;
+    let mut result = FunctionHeader::default();
+    for (name, value) in header {
+        match name.as_str() {
+            "time" => {
+                result.time = Some(value.as_u64()
+                    .ok_or(r#"header field "time" must be an unsigned integer (milliseconds)"#.to_string())?);
+            },
+            "expire" => {
+                let expire = value.as_u64()
+                    .ok_or(r#"header field "expire" must be an unsigned integer (seconds)"#.to_string())?;
+                result.expire = Some(u32::try_from(expire)
+                    .map_err(|_| r#"header field "expire" is out of u32 range"#.to_string())?);
+            },
+            "pubkey" => {
+                let pubkey = value.as_str()
+                    .ok_or(r#"header field "pubkey" must be a string"#.to_string())?;
+                let pubkey = pubkey.trim_start_matches("0x");
+                if pubkey.len() != 64 || hex::decode(pubkey).is_err() {
+                    return Err(r#"header field "pubkey" must be a 32-byte hex string"#.to_string());
+                }
+                result.pubkey = Some(pubkey.to_owned());
+            },
+            _ => return Err(format!(r#"unknown header field "{}""#, name)),
+        }
+    }
+    Ok(result)
+}
+
+/// Parses absolute expiration time of the message (unix time in seconds), it must be in the future.
+pub fn parse_expire_at(expire_at: &str) -> Result<u32, String> {
+    let expire_at = u32::from_str_radix(expire_at, 10)
+        .map_err(|e| format!("failed to parse expire_at: {}", e))?;
+    let now = now()?;
+    if expire_at <= now {
+        return Err(format!("expire_at {} is not in the future, current time is {}", expire_at, now));
+    }
+    Ok(expire_at)
+}
+
+/// Sets absolute expiration time to the header override, if it doesn't specify other expiration time.
+pub fn apply_expire_at(header: Option<FunctionHeader>, expire_at: u32) -> Result<FunctionHeader, String> {
+    let mut header = header.unwrap_or_default();
+    match header.expire {
+        Some(expire) if expire != expire_at => Err(format!(
+            r#"expire_at {} conflicts with "expire" field {} of the header override"#, expire_at, expire)),
+        _ => {
+            header.expire = Some(expire_at);
+            Ok(header)
+        },
+    }
+}
+
+/// Creates boc of an empty uninitialized account with the given address, which is used
+/// to emulate messages to accounts that don't exist yet.
+pub fn dummy_account_boc(addr: &str) -> Result<String, String> {
+    let addr = ton_block::MsgAddressInt::from_str(addr)
+        .map_err(|e| format!("couldn't decode address: {}", e))?;
+    Ok(base64::encode(
+        &ton_types::cells_serialization::serialize_toc(
+            &Account::with_address(addr)
+                .serialize()
+                .map_err(|e| format!("couldn't create dummy account for deploy emulation: {}", e))?
+        ).map_err(|e| format!("failed to serialize account cell: {}", e))?
+    ))
+}
+
+/// Fees of the local run along with the abort reason if the emulated transaction was aborted,
+/// the account balance if it was overridden for the run and the value attached to the call if fees
+/// are to be shown as its percentage.
+pub struct LocalRunFees {
+    pub fees: TransactionFees,
+    pub abort_reason: Option<String>,
+    pub balance_override: Option<u64>,
+    pub value: Option<u64>,
+}
+
+fn transaction_abort_reason(transaction: &Value) -> Option<String> {
+    if !transaction["aborted"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    let compute = &transaction["compute"];
+    if compute["success"].as_bool() == Some(false) {
+        return Some(format!("compute phase failed with exit code {}", compute["exit_code"]));
+    }
+    let action = &transaction["action"];
+    if action["success"].as_bool() == Some(false) {
+        return Some(format!("action phase failed with result code {}", action["result_code"]));
+    }
+    Some("transaction was aborted".to_string())
+}
+
+pub fn print_local_run_fees(fees: &LocalRunFees) {
+    let value = fees.value.filter(|value| *value != 0);
+    if fees.abort_reason.is_none() && fees.balance_override.is_none() && value.is_none() {
+        return print_fees(&fees.fees);
+    }
+    let mut result = json!({
+        "in_msg_fwd_fee": fees.fees.in_msg_fwd_fee.to_string(),
+        "storage_fee": fees.fees.storage_fee.to_string(),
+        "gas_fee": fees.fees.gas_fee.to_string(),
+        "out_msgs_fwd_fee": fees.fees.out_msgs_fwd_fee.to_string(),
+        "total_account_fees": fees.fees.total_account_fees.to_string(),
+        "total_output": fees.fees.total_output.to_string(),
+    });
+    if let Some(reason) = &fees.abort_reason {
+        result["aborted"] = json!(true);
+        result["abort_reason"] = json!(reason);
+    }
+    if let Some(balance) = fees.balance_override {
+        result["balance_override"] = json!(balance.to_string());
+    }
+    if let Some(value) = value {
+        result["value"] = json!(value.to_string());
+        result["percent_of_value"] = fees_percent_of_value(&fees.fees, value);
+    }
+    println!("{:#}", result);
+}
+
+fn fees_percent_of_value(fees: &TransactionFees, value: u64) -> Value {
+    let percent = |fee: u64| format!("{:.2}%", fee as f64 * 100.0 / value as f64);
+    json!({
+        "in_msg_fwd_fee": percent(fees.in_msg_fwd_fee),
+        "storage_fee": percent(fees.storage_fee),
+        "gas_fee": percent(fees.gas_fee),
+        "out_msgs_fwd_fee": percent(fees.out_msgs_fwd_fee),
+        "total_account_fees": percent(fees.total_account_fees),
+    })
+}
+
+// Value attached to the call (in nanotons), taken from the "value" argument of the function.
+fn attached_value(params: &str) -> Option<u64> {
+    let params: Value = serde_json::from_str(params).ok()?;
+    match &params[VALUE_PARAM_NAME] {
+        Value::Number(value) => value.as_u64(),
+        Value::String(value) => match value.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => u64::from_str(value).ok(),
+        },
+        _ => None,
+    }
+}
+
+// replaces balance of the account (in nanotons) for the local run
+fn override_account_balance(boc: &str, balance: u64) -> Result<String, String> {
+    let mut account = Account::construct_from_base64(boc)
+        .map_err(|e| format!("failed to construct account: {}", e))?;
+    account.set_balance(CurrencyCollection::with_grams(balance));
+    Ok(base64::encode(
+        &ton_types::cells_serialization::serialize_toc(
+            &account.serialize()
+                .map_err(|e| format!("failed to serialize account: {}", e))?
+        ).map_err(|e| format!("failed to serialize account cell: {}", e))?
+    ))
+}
+
+pub fn print_fees(fees: &TransactionFees) {
+    println!("{{");
+    println!("  \"in_msg_fwd_fee\": \"{}\",", fees.in_msg_fwd_fee);
+    println!("  \"storage_fee\": \"{}\",", fees.storage_fee);
+    println!("  \"gas_fee\": \"{}\",", fees.gas_fee);
+    println!("  \"out_msgs_fwd_fee\": \"{}\",", fees.out_msgs_fwd_fee);
+    println!("  \"total_account_fees\": \"{}\",", fees.total_account_fees);
+    println!("  \"total_output\": \"{}\"", fees.total_output);
+    println!("}}");
+}
+
+/// Estimates forward fee of the message sent from `src_wc` to `dst_wc` workchain using the forward
+/// prices from the blockchain config (masterchain prices are used if any of workchains is masterchain).
+pub fn estimate_forward_fee(bc_config: &BlockchainConfig, src_wc: i32, dst_wc: i32, message: &str) -> Result<u64, String> {
+    let bytes = base64::decode(message)
+        .map_err(|e| format!("failed to decode message boc from base64: {}", e))?;
+    let cell = ton_types::cells_serialization::deserialize_tree_of_cells(&mut std::io::Cursor::new(&bytes))
+        .map_err(|e| format!("failed to deserialize message boc: {}", e))?;
+
+    // root cell is not counted in forward fee
+    let mut visited = HashSet::new();
+    let mut stack = vec![];
+    for i in 0..cell.references_count() {
+        stack.push(cell.reference(i).map_err(|e| format!("failed to load message cell: {}", e))?);
+    }
+    let (mut bits, mut cells) = (0u128, 0u128);
+    while let Some(cell) = stack.pop() {
+        if !visited.insert(cell.repr_hash()) {
+            continue;
+        }
+        bits += cell.bit_length() as u128;
+        cells += 1;
+        for i in 0..cell.references_count() {
+            stack.push(cell.reference(i).map_err(|e| format!("failed to load message cell: {}", e))?);
+        }
+    }
+
+    let prices = bc_config.get_fwd_prices(src_wc == -1 || dst_wc == -1);
+    let fee = prices.lump_price as u128
+        + ((prices.bit_price as u128 * bits + prices.cell_price as u128 * cells + 0xffff) >> 16);
+    u64::try_from(fee).map_err(|_| "forward fee is out of u64 range".to_string())
+}
+
+// Sets source address of the external inbound message.
+fn set_message_src(msg: &str, src: &str) -> Result<String, String> {
+    let src = parse_ext_address(src)?;
+    let mut message = Message::construct_from_base64(msg)
+        .map_err(|e| format!("failed to construct message: {}", e))?;
+    match message.header_mut() {
+        CommonMsgInfo::ExtInMsgInfo(header) => header.src = src,
+        _ => return Err("source address can be set for external inbound messages only".to_string()),
+    }
+    tree_of_cells_into_base64(Some(&message.serialize()
+        .map_err(|e| format!("failed to serialize message: {}", e))?))
+}
+
+/// Runs the message on the account state with the local executor.
+pub async fn execute_locally(
+    ton: TonClient,
+    state: String,
+    msg: String,
+    unlimited_balance: Option<bool>,
+    now: Option<u32>,
+    bc_config: Option<String>,
+    skip_transaction_check: Option<bool>,
+) -> Result<ResultOfRunExecutor, String> {
+    run_executor(
+        ton,
+        ParamsOfRunExecutor {
+            message: msg,
+            account: AccountForExecutor::Account {
+                boc: state,
+                unlimited_balance,
+            },
+            execution_options: Some(ExecutionOptions {
+                block_time: now,
+                blockchain_config: bc_config,
+                ..Default::default()
+            }),
+            skip_transaction_check,
+            ..Default::default()
+        },
+    )
+    .await
+    .map_err(|e| format!("{:#}", e))
+}
+
+/// Options of the local emulation of the message by `emulate_locally`.
+#[derive(Default)]
+pub struct EmulateOptions<'a> {
+    /// Emulation is done to calculate fees, which are printed instead of the run status.
+    pub is_fee: bool,
+    /// Use a dummy account with unlimited balance if the account doesn't exist.
+    pub allow_dummy: bool,
+    /// Block time of the emulation, real time is used if not specified.
+    pub now: Option<u32>,
+    /// Blockchain config boc, network config is used if not specified.
+    pub bc_config: Option<String>,
+    /// Print fees even if the transaction is aborted.
+    pub fees_on_abort: bool,
+    /// Account balance used instead of the real one.
+    pub balance: Option<u64>,
+    /// Source address of the external inbound message.
+    pub src: Option<&'a str>,
+    /// Value attached to the call, fees are additionally printed as a percentage of it.
+    pub value: Option<u64>,
+    /// Use unlimited account balance for the emulation.
+    pub unlimited_balance: bool,
+}
+
 pub async fn emulate_locally(
     ton: TonClient,
     addr: &str,
     msg: String,
-    is_fee: bool,
+    options: EmulateOptions<'_>,
 ) -> Result<(), String> {
+    let EmulateOptions {
+        is_fee, allow_dummy, now, bc_config, fees_on_abort, balance, src, value, unlimited_balance,
+    } = options;
+    let msg = match src {
+        Some(src) => set_message_src(&msg, src)?,
+        None => msg,
+    };
     let state: String;
+    let mut dummy_used = false;
     let state_boc = query_account_field(ton.clone(), addr, "boc").await;
     if state_boc.is_err() {
-        if is_fee {
-            let addr = ton_block::MsgAddressInt::from_str(addr)
-                .map_err(|e| format!("couldn't decode address: {}", e))?;
-            state = base64::encode(
-                &ton_types::cells_serialization::serialize_toc(
-                    &Account::with_address(addr)
-                        .serialize()
-                        .map_err(|e| format!("couldn't create dummy account for deploy emulation: {}", e))?
-                ).map_err(|e| format!("failed to serialize account cell: {}", e))?
-            );
+        if is_fee || allow_dummy {
+            state = dummy_account_boc(addr)?;
+            dummy_used = true;
         } else {
             return Err(state_boc.err().unwrap());
         }
     } else {
         state = state_boc.unwrap();
     }
-    let res = run_executor(
+    let state = match balance {
+        Some(balance) => override_account_balance(&state, balance)?,
+        None => state,
+    };
+    let unlimited_balance = balance.is_none() && unlimited_balance;
+    let res = execute_locally(
         ton.clone(),
+        state,
+        msg.clone(),
+        if balance.is_none() && (is_fee || dummy_used || unlimited_balance) {
+            Some(true)
+        } else {
+            None
+        },
+        now,
+        bc_config,
+        if is_fee && fees_on_abort {
+            Some(true)
+        } else {
+            None
+        },
+    )
+    .await;
+
+    if res.is_err() {
+        return Err(res.err().unwrap());
+    }
+    if is_fee {
+        let res = res.unwrap();
+        print_local_run_fees(&LocalRunFees {
+            abort_reason: transaction_abort_reason(&res.transaction),
+            balance_override: balance,
+            fees: res.fees,
+            value,
+        });
+    } else {
+        if let Some(balance) = balance {
+            println!("Local run used account balance overridden to {} nanotons.", balance);
+        }
+        if dummy_used {
+            println!("Account doesn't exist, local run used a dummy account with unlimited balance.");
+        } else if unlimited_balance {
+            println!("Local run used unlimited account balance.");
+        }
+        println!("Local run succeeded. Executing onchain."); // TODO: check is_json
+    }
+    Ok(())
+}
+
+/// Executes the message on the account directly with the transaction executor, without any SDK
+/// client, so it can be used in tests and on air-gapped machines. `account` and `msg` are account
+/// and message bocs encoded in base64. Returns the transaction along with the same fee info as
+/// `emulate_locally`.
+pub fn emulate_locally_offline(
+    account: &str,
+    msg: &str,
+    bc_config: BlockchainConfig,
+    now: Option<u32>,
+    balance: Option<u64>,
+) -> Result<(Transaction, LocalRunFees), String> {
+    let account = match balance {
+        Some(balance) => override_account_balance(account, balance)?,
+        None => account.to_owned(),
+    };
+    let account = Account::construct_from_base64(&account)
+        .map_err(|e| format!("failed to construct account: {}", e))?;
+    let last_tr_lt = account.last_tr_time().unwrap_or(0);
+    let mut account_root = account.serialize()
+        .map_err(|e| format!("failed to serialize account: {}", e))?;
+    let message = Message::construct_from_base64(msg)
+        .map_err(|e| format!("failed to construct message: {}", e))?;
+
+    let block_unixtime = now.unwrap_or_else(|| (now_ms() / 1000) as u32);
+    let block_lt = std::cmp::max(last_tr_lt + 1, block_unixtime as u64 * 1000);
+    let params = ExecuteParams {
+        block_unixtime,
+        block_lt,
+        last_tr_lt: Arc::new(AtomicU64::new(last_tr_lt)),
+        ..ExecuteParams::default()
+    };
+    let executor = OrdinaryTransactionExecutor::new(bc_config);
+    let transaction = executor.execute_with_libs_and_params(Some(&message), &mut account_root, params)
+        .map_err(|e| format!("local run failed: {}", e))?;
+
+    let (fees, abort_reason) = transaction_fees(&transaction)?;
+    Ok((transaction, LocalRunFees { fees, abort_reason, balance_override: balance, value: None }))
+}
+
+// Computes fees of the transaction the same way as `run_executor` does, and the abort reason if the
+// transaction was aborted.
+fn transaction_fees(transaction: &Transaction) -> Result<(TransactionFees, Option<String>), String> {
+    let desc = match transaction.read_description()
+        .map_err(|e| format!("failed to read transaction description: {}", e))? {
+        TransactionDescr::Ordinary(desc) => desc,
+        _ => return Err("unexpected transaction type".to_string()),
+    };
+    let storage_fee = desc.storage_ph.as_ref()
+        .map(|phase| phase.storage_fees_collected.as_u128())
+        .unwrap_or(0) as u64;
+    let (gas_fee, compute_failure) = match &desc.compute_ph {
+        TrComputePhase::Vm(phase) => (
+            phase.gas_fees.as_u128() as u64,
+            (!phase.success).then(|| format!("compute phase failed with exit code {}", phase.exit_code)),
+        ),
+        TrComputePhase::Skipped(_) => (0, None),
+    };
+    let (out_msgs_fwd_fee, action_fees, action_failure) = match &desc.action {
+        Some(phase) => (
+            phase.total_fwd_fees.as_ref().map(|fee| fee.as_u128()).unwrap_or(0) as u64,
+            phase.total_action_fees.as_ref().map(|fee| fee.as_u128()).unwrap_or(0) as u64,
+            (!phase.success).then(|| format!("action phase failed with result code {}", phase.result_code)),
+        ),
+        None => (0, 0, None),
+    };
+    let total_account_fees = transaction.total_fees().grams.as_u128() as u64;
+    let mut total_output = 0u64;
+    transaction.iterate_out_msgs(|msg| {
+        total_output += msg.get_value().map(|value| value.grams.as_u128() as u64).unwrap_or(0);
+        Ok(true)
+    }).map_err(|e| format!("failed to read outbound messages: {}", e))?;
+
+    let abort_reason = if desc.aborted {
+        compute_failure.or(action_failure).or_else(|| Some("transaction was aborted".to_string()))
+    } else {
+        None
+    };
+    let fees = TransactionFees {
+        in_msg_fwd_fee: total_account_fees.saturating_sub(storage_fee + gas_fee + action_fees),
+        storage_fee,
+        gas_fee,
+        out_msgs_fwd_fee,
+        total_account_fees,
+        total_output,
+        ..Default::default()
+    };
+    Ok((fees, abort_reason))
+}
+
+// Runs the message on the current account state locally and returns the decoded output of the function.
+async fn emulate_call_output(
+    ton: TonClient,
+    addr: &str,
+    msg: String,
+    abi: Abi,
+    bc_config: Option<String>,
+) -> Result<Value, String> {
+    let state = query_account_field(ton.clone(), addr, "boc").await?;
+    let res = run_executor(
+        ton,
         ParamsOfRunExecutor {
-            message: msg.clone(),
-            account: AccountForExecutor::Account {
-                boc: state,
-                unlimited_balance: if is_fee {
-                    Some(true)
-                } else {
-                    None
-                },
+            message: msg,
+            account: AccountForExecutor::Account { boc: state, unlimited_balance: None },
+            abi: Some(abi),
+            execution_options: Some(ExecutionOptions {
+                blockchain_config: bc_config,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    ).await
+        .map_err(|e| format!("local run failed: {:#}", e))?;
+    Ok(res.decoded.and_then(|d| d.output).unwrap_or(json!({})))
+}
+
+// Removes fields which are added to the output by the cli itself. Fields present in the `decoded`
+// output are outputs of the function, so they are kept even if their names look like the extra ones.
+fn declared_output(output: &Value, decoded: &Value) -> Value {
+    match output {
+        Value::Object(fields) => Value::Object(fields.iter()
+            .filter(|(name, _)| !is_extra_output_field(name) || decoded.get(name.as_str()).is_some())
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect()),
+        output => output.clone(),
+    }
+}
+
+/// Runs the call locally on the current account state and then sends the same message on-chain,
+/// and compares decoded outputs of both executions. Mismatch means that the local executor or
+/// blockchain config used for the local run differ from the network ones (or the contract is not
+/// deterministic).
+pub async fn compare_local_and_onchain(
+    config: &Config,
+    addr: &str,
+    abi_path: &str,
+    method: &str,
+    params: &str,
+    keys: Option<String>,
+) -> Result<(), String> {
+    check_method_exists(abi_path, method, config).await?;
+    let ton = create_client_verbose(config)?;
+    let abi = load_abi(abi_path, config).await?;
+    let msg_params = prepare_message_params(addr, abi.clone(), method, params, None, keys)?;
+    let msg = encode_message(ton.clone(), msg_params).await
+        .map_err(|e| format!("failed to create inbound message: {}", e))?;
+
+    let local = emulate_call_output(ton.clone(), addr, msg.message.clone(), abi.clone(), fee_blockchain_config(config)?).await?;
+    if !config.is_json {
+        println!("Local run succeeded. Executing onchain.");
+    }
+    let onchain = declared_output(&send_message_and_wait(ton, Some(abi), msg.message, config).await?, &local);
+
+    let mut diff = vec![];
+    collect_result_diff("output", &onchain, &local, true, &mut diff);
+    if config.is_json {
+        println!("{:#}", json!({
+            "local": local,
+            "onchain": onchain,
+            "match": diff.is_empty(),
+        }));
+    } else {
+        println!("Local output: {:#}", local);
+        println!("On-chain output: {:#}", onchain);
+    }
+    if !diff.is_empty() {
+        return Err(format!("local and on-chain outputs differ (expected is local):\n{}", diff.join("\n")));
+    }
+    if !config.is_json {
+        println!("Local and on-chain outputs match.");
+    }
+    Ok(())
+}
+
+async fn last_masterchain_seq_no(ton: TonClient) -> Result<u64, String> {
+    let blocks = query_with_limit(
+        ton,
+        "blocks",
+        json!({ "workchain_id": { "eq": -1 } }),
+        "seq_no",
+        Some(vec![OrderBy{ path: "seq_no".to_owned(), direction: SortDirection::DESC }]),
+        Some(1),
+    ).await
+        .map_err(|e| format!("failed to query last masterchain block: {}", e))?;
+    blocks.first()
+        .and_then(|block| block["seq_no"].as_u64())
+        .ok_or_else(|| "failed to get seq_no of the last masterchain block".to_string())
+}
+
+/// Waits until `config.wait_blocks` masterchain blocks are produced after the current one.
+/// Does nothing if `wait_blocks` is 0.
+pub async fn wait_for_masterchain_blocks(ton: TonClient, config: &Config) -> Result<(), String> {
+    if config.wait_blocks == 0 {
+        return Ok(());
+    }
+    let mut seq_no = last_masterchain_seq_no(ton.clone()).await?;
+    let target = seq_no + config.wait_blocks as u64;
+    if !config.is_json {
+        println!("Waiting for {} masterchain blocks...", config.wait_blocks);
+    }
+    while seq_no < target {
+        let block = ton_client::net::wait_for_collection(
+            ton.clone(),
+            ParamsOfWaitForCollection {
+                collection: "blocks".to_owned(),
+                filter: Some(json!({
+                    "workchain_id": { "eq": -1 },
+                    "seq_no": { "gt": seq_no }
+                })),
+                result: "seq_no".to_owned(),
+                timeout: Some(config.timeout),
+                ..Default::default()
             },
+        ).await
+            .map_err(|e| format!("failed to wait for masterchain block: {}", e))?;
+        seq_no = block.result["seq_no"].as_u64()
+            .ok_or_else(|| "failed to get seq_no of the masterchain block".to_string())?;
+    }
+    Ok(())
+}
+
+// prints elapsed time and the shard block being scanned while waiting for the transaction
+fn print_wait_heartbeat(start: Instant, event: &ProcessingEvent) {
+    if let ProcessingEvent::WillFetchNextBlock { shard_block_id, .. } = event {
+        println!("Waiting for transaction... {}s elapsed, scanning blocks after {}",
+            start.elapsed().as_secs(), shard_block_id);
+    }
+}
+
+async fn processing_result_output(
+    ton: TonClient,
+    result: ResultOfProcessMessage,
+    abi: Option<Abi>,
+    config: &Config,
+) -> Value {
+    let in_msg = result.transaction["in_msg"].as_str().map(|id| id.to_owned());
+    let events = decode_events(&result);
+    let mut output = result.decoded.and_then(|d| d.output).unwrap_or(json!({}));
+    if let Some(output) = output.as_object_mut() {
+        if !events.is_empty() {
+            let name = extra_field_name(output, "events");
+            output.insert(name, json!(events));
+        }
+        if config.include_boc {
+            let name = extra_field_name(output, "out_messages_boc");
+            output.insert(name, json!(result.out_messages));
+        }
+        if config.tree_depth > 0 {
+            if let Some(in_msg) = in_msg {
+                let tree = query_call_tree(ton, &in_msg, abi, config.tree_depth, config).await
+                    .unwrap_or_else(|e| json!({ "Error": e }));
+                let name = extra_field_name(output, "transaction_tree");
+                output.insert(name, tree);
+            }
+        }
+    }
+    output
+}
+
+// Fields which are added to the decoded output by the cli itself.
+const EXTRA_OUTPUT_FIELDS: [&str; 3] = ["events", "out_messages_boc", "transaction_tree"];
+
+// Name for the extra field which doesn't overwrite the function output with the same name: `_` is
+// appended until the name is free.
+fn extra_field_name(output: &serde_json::Map<String, Value>, name: &str) -> String {
+    let mut name = name.to_owned();
+    while output.contains_key(&name) {
+        name.push('_');
+    }
+    name
+}
+
+fn is_extra_output_field(name: &str) -> bool {
+    EXTRA_OUTPUT_FIELDS.contains(&name.trim_end_matches('_'))
+}
+
+/// Checks that fields of the decoded output match outputs declared for the function in the ABI.
+/// Mismatch usually means that the ABI doesn't match the called contract.
+pub fn check_output_params(output: &Value, function: &ton_abi::Function) -> Result<(), String> {
+    let empty = serde_json::Map::new();
+    let fields = match output {
+        Value::Object(fields) => fields,
+        Value::Null => &empty,
+        _ => return Err(format!(r#"output of function "{}" is not an object"#, function.name)),
+    };
+    let declared = function.output_params().iter().map(|param| param.name.clone()).collect::<HashSet<_>>();
+    let mut errors = vec![];
+    for param in function.output_params() {
+        if !fields.contains_key(&param.name) {
+            errors.push(format!(r#"output "{}" of type "{}" is missing"#, param.name, param.kind));
+        }
+    }
+    for name in fields.keys() {
+        if !declared.contains(name) && !is_extra_output_field(name) {
+            errors.push(format!(r#"output "{}" is not declared in the ABI"#, name));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!(r#"output of function "{}" doesn't match the ABI (wrong ABI?): {}"#,
+            function.name, errors.join(", ")));
+    }
+    Ok(())
+}
+
+// Collects events (external outbound messages) emitted by the transaction. Bodies decoded with the ABI
+// are shown as event name and parameters, bodies which don't match any ABI event are shown as raw boc.
+fn decode_events(result: &ResultOfProcessMessage) -> Vec<Value> {
+    let decoded = result.decoded.as_ref().map(|d| d.out_messages.as_slice()).unwrap_or_default();
+    result.out_messages.iter().enumerate()
+        .filter_map(|(i, boc)| {
+            let msg = Message::construct_from_base64(boc).ok()?;
+            if msg.is_internal() {
+                return None;
+            }
+            match decoded.get(i).and_then(|body| body.as_ref()) {
+                Some(body) if matches!(body.body_type, MessageBodyType::Event) =>
+                    Some(json!({ "name": body.name, "value": body.value })),
+                _ => Some(json!({
+                    "raw": tree_of_cells_into_base64(msg.body().map(|slice| slice.into_cell()).as_ref())
+                        .unwrap_or_default()
+                })),
+            }
+        })
+        .collect()
+}
+
+/// Fetches the tree of transactions triggered by the inbound message and decodes message bodies with the ABI.
+/// Transactions deeper than `depth` hops from the first one are not included.
+async fn query_call_tree(
+    ton: TonClient,
+    in_msg: &str,
+    abi: Option<Abi>,
+    depth: u32,
+    config: &Config,
+) -> Result<Value, String> {
+    let tree = query_transaction_tree(
+        ton,
+        ParamsOfQueryTransactionTree {
+            in_msg: in_msg.to_owned(),
+            abi_registry: abi.map(|abi| vec![abi]),
+            timeout: Some(config.timeout),
             ..Default::default()
         },
-    )
-    .await;
+    ).await
+        .map_err(|e| format!("failed to query transaction tree: {}", e))?;
+    let messages = tree.messages.iter()
+        .filter_map(|msg| serde_json::to_value(msg).ok())
+        .map(|msg| (msg["id"].as_str().unwrap_or_default().to_owned(), msg))
+        .collect::<HashMap<_, _>>();
+    let transactions = tree.transactions.iter()
+        .filter_map(|tr| serde_json::to_value(tr).ok())
+        .map(|tr| (tr["in_msg"].as_str().unwrap_or_default().to_owned(), tr))
+        .collect::<HashMap<_, _>>();
+    Ok(call_tree_node(in_msg, &messages, &transactions, depth))
+}
 
-    if res.is_err() {
-        return Err(format!("{:#}", res.err().unwrap()));
+// builds node of the call tree for the message and the transaction it triggered
+fn call_tree_node(
+    msg_id: &str,
+    messages: &HashMap<String, Value>,
+    transactions: &HashMap<String, Value>,
+    depth: u32,
+) -> Value {
+    let mut node = messages.get(msg_id).cloned().unwrap_or(json!({ "id": msg_id }));
+    if depth == 0 {
+        return node;
     }
-    if is_fee {
-        let fees = res.unwrap().fees;
-        println!("{{");
-        println!("  \"in_msg_fwd_fee\": \"{}\",", fees.in_msg_fwd_fee);
-        println!("  \"storage_fee\": \"{}\",", fees.storage_fee);
-        println!("  \"gas_fee\": \"{}\",", fees.gas_fee);
-        println!("  \"out_msgs_fwd_fee\": \"{}\",", fees.out_msgs_fwd_fee);
-        println!("  \"total_account_fees\": \"{}\",", fees.total_account_fees);
-        println!("  \"total_output\": \"{}\"", fees.total_output);
-        println!("}}");
-    } else {
-        println!("Local run succeeded. Executing onchain."); // TODO: check is_json
+    if let Some(tr) = transactions.get(msg_id) {
+        let out_messages = tr["out_msgs"].as_array().cloned().unwrap_or_default()
+            .iter()
+            .filter_map(|id| id.as_str())
+            .map(|id| call_tree_node(id, messages, transactions, depth - 1))
+            .collect::<Vec<_>>();
+        let mut tr = tr.clone();
+        tr["out_messages"] = json!(out_messages);
+        node["transaction"] = tr;
+    }
+    node
+}
+
+/// Error of the call which keeps the SDK error as is, so that its code and data can be handled
+/// programmatically. Other errors are kept as messages.
+#[derive(Debug)]
+pub enum CallError {
+    Client(ClientError),
+    Other(String),
+}
+
+impl std::fmt::Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallError::Client(e) => write!(f, "{:#}", e),
+            CallError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<String> for CallError {
+    fn from(e: String) -> Self {
+        CallError::Other(e)
+    }
+}
+
+impl From<ClientError> for CallError {
+    fn from(e: ClientError) -> Self {
+        CallError::Client(e)
     }
-    Ok(())
 }
 
 pub async fn send_message_and_wait(
@@ -175,10 +1591,22 @@ pub async fn send_message_and_wait(
     msg: String,
     config: &Config,
 ) -> Result<Value, String> {
+    try_send_message_and_wait(ton, abi, msg, config).await
+        .map_err(|e| e.to_string())
+}
+
+/// The same as `send_message_and_wait`, but SDK errors are returned as `ClientError`.
+pub async fn try_send_message_and_wait(
+    ton: TonClient,
+    abi: Option<Abi>,
+    msg: String,
+    config: &Config,
+) -> Result<Value, CallError> {
 
     if !config.is_json {
         println!("Processing... ");
     }
+    let start = Instant::now();
     let callback = |_| {
         async move {}
     };
@@ -192,60 +1620,516 @@ pub async fn send_message_and_wait(
         },
         callback,
     ).await
-        .map_err(|e| format!("{:#}", e))?;
+        .map_err(|e| {
+            metrics::record_call(None, start.elapsed());
+            e
+        })?;
 
+    if !config.async_call && config.wait_level == "Accepted" {
+        metrics::record_call(Some(0), start.elapsed());
+        return Ok(json!({
+            "message_id": message_id(&msg),
+            "shard_block_id": result.shard_block_id,
+        }));
+    }
     if !config.async_call {
-        let result = wait_for_transaction(
+        if config.poll_interval_ms != 0 {
+            let polling = poll_transaction_result(ton.clone(), &msg, abi.clone(), config);
+            let result = tokio::select! {
+                result = polling => result,
+                _ = tokio::signal::ctrl_c() => {
+                    return Err(interrupted_error(message_id(&msg)).into());
+                },
+            };
+            metrics::record_processing(&result, start);
+            let result = result?;
+            wait_for_masterchain_blocks(ton.clone(), config).await?;
+            return Ok(processing_result_output(ton, result, abi, config).await);
+        }
+        let is_json = config.is_json;
+        let wait_callback = move |event| { async move {
+            if !is_json {
+                print_wait_heartbeat(start, &event);
+            }
+        }};
+        let waiting = wait_for_transaction(
             ton.clone(),
             ParamsOfWaitForTransaction {
-                abi,
+                abi: abi.clone(),
                 message: msg.clone(),
                 shard_block_id: result.shard_block_id,
                 send_events: true,
                 ..Default::default()
             },
-            callback,
-        ).await
-            .map_err(|e| format!("{:#}", e))?;
-        Ok(result.decoded.and_then(|d| d.output).unwrap_or(json!({})))
+            wait_callback,
+        );
+        let result = tokio::select! {
+            result = waiting => result,
+            _ = tokio::signal::ctrl_c() => {
+                return Err(interrupted_error(message_id(&msg)).into());
+            },
+        };
+        metrics::record_processing(&result, start);
+        let result = result?;
+        wait_for_masterchain_blocks(ton.clone(), config).await?;
+        Ok(processing_result_output(ton, result, abi, config).await)
     } else {
+        metrics::record_call(Some(0), start.elapsed());
         Ok(json!({}))
     }
 }
 
+fn message_id(msg: &str) -> Option<String> {
+    Message::construct_from_base64(msg)
+        .and_then(|msg| msg.serialize())
+        .map(|cell| hex::encode(cell.repr_hash().as_slice()))
+        .ok()
+}
+
+// Waits for the transaction of the sent message by polling instead of the SDK waiting. The result
+// is in the same form as the SDK returns it.
+async fn poll_transaction_result(
+    ton: TonClient,
+    msg: &str,
+    abi: Option<Abi>,
+    config: &Config,
+) -> Result<ResultOfProcessMessage, ClientError> {
+    let transaction = poll_for_transaction(ton.clone(), msg, config).await?;
+    polled_transaction_result(ton, transaction, abi).await
+}
+
+// Checks every `config.poll_interval_ms` whether the transaction of the message has appeared
+// and returns it instead of waiting for it with the SDK.
+async fn poll_for_transaction(ton: TonClient, msg: &str, config: &Config) -> Result<Value, ClientError> {
+    let message_id = message_id(msg)
+        .ok_or_else(|| polling_error("failed to calculate message id".to_string()))?;
+    let start = Instant::now();
+    loop {
+        let transactions = query_with_limit(
+            ton.clone(),
+            "transactions",
+            json!({ "in_msg": { "eq": message_id } }),
+            "id in_msg aborted boc",
+            None,
+            Some(1),
+        ).await?;
+        if let Some(transaction) = transactions.into_iter().next() {
+            return Ok(transaction);
+        }
+        if start.elapsed().as_millis() >= config.timeout as u128 {
+            return Err(ClientError {
+                code: POLL_TIMEOUT_CODE,
+                message: format!("transaction of the message {} was not found in {} ms. The message may still \
+                    be processed", message_id, config.timeout),
+                data: json!({
+                    "status": "pending",
+                    "message_id": message_id,
+                }),
+            });
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(config.poll_interval_ms as u64)).await;
+    }
+}
+
+// Builds the processing result from the polled transaction in the same form as the SDK waiting returns it:
+// fees are computed from the transaction boc, and outbound messages are decoded with the ABI.
+async fn polled_transaction_result(
+    ton: TonClient,
+    transaction: Value,
+    abi: Option<Abi>,
+) -> Result<ResultOfProcessMessage, ClientError> {
+    let boc = transaction["boc"].as_str()
+        .ok_or_else(|| polling_error("transaction boc is missing in the query result".to_string()))?;
+    let parsed = Transaction::construct_from_base64(boc)
+        .map_err(|e| polling_error(format!("failed to parse transaction: {}", e)))?;
+    let (fees, abort_reason) = transaction_fees(&parsed).map_err(polling_error)?;
+    if let Some(reason) = abort_reason {
+        return Err(aborted_transaction_error(&parsed, transaction["id"].as_str().unwrap_or_default(), reason));
+    }
+    let mut out_messages = vec![];
+    parsed.iterate_out_msgs(|msg| {
+        out_messages.push(base64::encode(&msg.write_to_bytes()?));
+        Ok(true)
+    }).map_err(|e| polling_error(format!("failed to read outbound messages: {}", e)))?;
+    let decoded = match abi {
+        Some(abi) => {
+            let mut bodies = vec![];
+            for message in &out_messages {
+                let body = decode_message(
+                    ton.clone(),
+                    ParamsOfDecodeMessage {
+                        abi: abi.clone(),
+                        message: message.clone(),
+                        ..Default::default()
+                    },
+                ).await.ok();
+                bodies.push(body);
+            }
+            let output = bodies.iter().flatten()
+                .find(|body| matches!(body.body_type, MessageBodyType::Output))
+                .map(|body| body.value.clone().unwrap_or(json!({})));
+            Some(DecodedOutput { out_messages: bodies, output })
+        },
+        None => None,
+    };
+    Ok(ResultOfProcessMessage { transaction, out_messages, decoded, fees })
+}
+
+// Error for the aborted polled transaction in the same form as the SDK returns it: execution error with
+// the exit code of the failed phase, or low balance error if the compute phase was skipped for lack of gas.
+fn aborted_transaction_error(transaction: &Transaction, id: &str, reason: String) -> ClientError {
+    let (code, mut data) = match transaction.read_description() {
+        Ok(TransactionDescr::Ordinary(desc)) => match (&desc.compute_ph, &desc.action) {
+            (TrComputePhase::Skipped(skipped), _) if matches!(skipped.reason, ComputeSkipReason::NoGas) => (
+                SDK_LOW_BALANCE_ERROR_CODE,
+                json!({ "phase": "computeSkipped", "reason": "NoGas" }),
+            ),
+            (TrComputePhase::Vm(phase), _) if !phase.success => (
+                SDK_EXECUTION_ERROR_CODE,
+                json!({ "phase": "computeVm", "exit_code": phase.exit_code, "exit_arg": phase.exit_arg }),
+            ),
+            (_, Some(action)) if !action.success => (
+                SDK_EXECUTION_ERROR_CODE,
+                json!({ "phase": "action", "exit_code": action.result_code }),
+            ),
+            _ => (SDK_EXECUTION_ERROR_CODE, json!({})),
+        },
+        _ => (SDK_EXECUTION_ERROR_CODE, json!({})),
+    };
+    data["transaction_id"] = json!(id);
+    ClientError {
+        code,
+        message: format!("transaction {} was aborted: {}", id, reason),
+        data,
+    }
+}
+
+fn polling_error(message: String) -> ClientError {
+    ClientError {
+        code: POLL_FAILED_CODE,
+        message,
+        data: json!({}),
+    }
+}
+
+// Error code which is returned if waiting for the transaction is interrupted with Ctrl-C.
+const WAIT_INTERRUPTED_CODE: u32 = 1;
+// Error code which is returned if the transaction is not found by polling in `timeout`.
+const POLL_TIMEOUT_CODE: u32 = 2;
+// Error code which is returned if the polled transaction can't be read.
+const POLL_FAILED_CODE: u32 = 3;
+
+// builds error for interrupted waiting, which contains id of the pending message (if it was sent),
+// so its processing can be checked later
+fn interrupted_error(message_id: Option<String>) -> ClientError {
+    let message = match &message_id {
+        Some(message_id) => format!("interrupted while waiting for the transaction. Message {} was sent \
+            and may still be processed", message_id),
+        None => "interrupted before the message was sent".to_owned(),
+    };
+    ClientError {
+        code: WAIT_INTERRUPTED_CODE,
+        message,
+        data: json!({
+            "status": "pending",
+            "message_id": message_id,
+        }),
+    }
+}
+
 pub async fn process_message(
     ton: TonClient,
     msg: ParamsOfEncodeMessage,
     config: &Config,
 ) -> Result<Value, ClientError> {
-    let callback = |event| { async move {
-        if let ProcessingEvent::DidSend { shard_block_id: _, message_id, message: _ } = event {
-            println!("MessageId: {}", message_id)
+    let start = Instant::now();
+    let is_json = config.is_json;
+    let sent_message_id = Arc::new(Mutex::new(None));
+    let sent_id = sent_message_id.clone();
+    let callback = move |event| {
+        let sent_id = sent_id.clone();
+        async move {
+            if let ProcessingEvent::DidSend { message_id, .. } = &event {
+                *sent_id.lock().unwrap() = Some(message_id.clone());
+                if !is_json {
+                    println!("MessageId: {}", message_id)
+                }
+            } else if !is_json {
+                print_wait_heartbeat(start, &event);
+            }
         }
-    }};
-    let res = if !config.is_json {
-        ton_client::processing::process_message(
-            ton.clone(),
-            ParamsOfProcessMessage {
-                message_encode_params: msg.clone(),
-                send_events: true,
-                ..Default::default()
-            },
-            callback,
-        ).await
-    } else {
-        ton_client::processing::process_message(
-            ton.clone(),
-            ParamsOfProcessMessage {
-                message_encode_params: msg.clone(),
-                send_events: true,
-                ..Default::default()
-            },
-            |_| { async move {} },
-        ).await
-    }?;
+    };
+    let processing = async {
+        if config.poll_interval_ms != 0 {
+            send_and_poll_message(ton.clone(), &msg, sent_message_id.clone(), config).await
+        } else {
+            ton_client::processing::process_message(
+                ton.clone(),
+                ParamsOfProcessMessage {
+                    message_encode_params: msg.clone(),
+                    send_events: true,
+                    ..Default::default()
+                },
+                callback,
+            ).await
+        }
+    };
+    let res = tokio::select! {
+        res = processing => res,
+        _ = tokio::signal::ctrl_c() => {
+            let message_id = sent_message_id.lock().unwrap().clone();
+            return Err(interrupted_error(message_id));
+        },
+    };
+    metrics::record_processing(&res, start);
+    let res = res?;
 
-    Ok(res.decoded.and_then(|d| d.output).unwrap_or(json!({})))
+    Ok(processing_result_output(ton, res, Some(msg.abi), config).await)
+}
+
+// Encodes and sends the message, then waits for its transaction by polling (see `poll_interval_ms`).
+async fn send_and_poll_message(
+    ton: TonClient,
+    msg: &ParamsOfEncodeMessage,
+    sent_message_id: Arc<Mutex<Option<String>>>,
+    config: &Config,
+) -> Result<ResultOfProcessMessage, ClientError> {
+    let encoded = encode_message(ton.clone(), msg.clone()).await?;
+    send_message(
+        ton.clone(),
+        ParamsOfSendMessage {
+            message: encoded.message.clone(),
+            abi: Some(msg.abi.clone()),
+            send_events: false,
+            ..Default::default()
+        },
+        |_| async {},
+    ).await?;
+    *sent_message_id.lock().unwrap() = Some(encoded.message_id.clone());
+    if !config.is_json {
+        println!("MessageId: {}", encoded.message_id);
+    }
+    poll_transaction_result(ton, &encoded.message, Some(msg.abi.clone()), config).await
+}
+
+// checks that encoded message doesn't exceed the size limit set in the config
+fn check_message_size(message: &str, config: &Config) -> Result<(), String> {
+    if config.max_message_size == 0 {
+        return Ok(());
+    }
+    let size = base64::decode(message)
+        .map_err(|e| format!("failed to decode message boc: {}", e))?
+        .len();
+    if size > config.max_message_size as usize {
+        return Err(format!("encoded message size {} bytes exceeds the limit of {} bytes (\"max_message_size\" in config)",
+            size, config.max_message_size));
+    }
+    Ok(())
+}
+
+// warns if function declares inputs, but its arguments are empty, which usually means
+// that they were lost while parsing
+fn warn_empty_params(abi: &Abi, method: &str, params: &str, config: &Config) {
+    if config.is_json {
+        return;
+    }
+    let inputs = match abi {
+        Abi::Contract(contract) => contract.functions.iter()
+            .find(|function| function.name == method)
+            .map(|function| function.inputs.len())
+            .unwrap_or(0),
+        _ => 0,
+    };
+    let is_empty = serde_json::from_str::<Value>(params)
+        .map(|params| params.as_object().map(|obj| obj.is_empty()).unwrap_or(false))
+        .unwrap_or(false);
+    if inputs > 0 && is_empty {
+        println!("Warning: function \"{}\" declares {} inputs, but no arguments are specified.", method, inputs);
+    }
+}
+
+const REDACTED_VALUE: &str = "<redacted>";
+// Parts of field names which may hold key material in the diagnostic output.
+const SENSITIVE_FIELD_NAMES: &[&str] = &["secret", "private", "signature", "phrase", "mnemonic"];
+
+fn is_sensitive_field(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SENSITIVE_FIELD_NAMES.iter().any(|sensitive| name.contains(sensitive))
+}
+
+/// Masks values of all fields which may hold private keys or signatures. Must be applied to every
+/// diagnostic json printed by the call path.
+pub fn redact_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (name, field) in map.iter_mut() {
+                if is_sensitive_field(name) && !field.is_null() {
+                    *field = json!(REDACTED_VALUE);
+                } else {
+                    redact_secrets(field);
+                }
+            }
+        },
+        Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {},
+    }
+}
+
+// Message encoding parameters for diagnostics. ABI is shown by its path, secrets are redacted.
+fn encode_params_dump(msg_params: &ParamsOfEncodeMessage, abi_path: &str) -> Result<Value, String> {
+    let mut dump = serde_json::to_value(msg_params)
+        .map_err(|e| format!("failed to serialize encode params: {}", e))?;
+    dump["abi"] = json!({ "path": abi_path });
+    redact_secrets(&mut dump);
+    Ok(dump)
+}
+
+// Prints the body of the message which failed to be encoded, so that it can be seen whether the
+// function call itself is encodable or the failure happened while building the message around it.
+async fn dump_failed_encode(ton: TonClient, msg_params: &ParamsOfEncodeMessage) {
+    let call_set = match msg_params.call_set.clone() {
+        Some(call_set) => call_set,
+        None => return,
+    };
+    let body = encode_message_body(
+        ton,
+        ParamsOfEncodeMessageBody {
+            abi: msg_params.abi.clone(),
+            call_set,
+            is_internal: false,
+            signer: msg_params.signer.clone(),
+            address: msg_params.address.clone(),
+            ..Default::default()
+        },
+    ).await;
+    match body {
+        Ok(body) => println!("Encoded body of the failed message: {}", body.body),
+        Err(e) => println!("Failed to encode body of the message: {}", e),
+    }
+}
+
+/// State init which is attached to the call message to deploy the uninitialized account.
+pub struct InitState {
+    pub tvc: String,
+    /// Public key which is set to the initial data. If not specified, public key of the signer is used.
+    pub pubkey: Option<String>,
+}
+
+impl InitState {
+    pub fn new(tvc: &str, pubkey: Option<&str>) -> Result<Self, String> {
+        let pubkey = pubkey.map(|pubkey| {
+            let key = pubkey.trim_start_matches("0x");
+            match hex::decode(key) {
+                Ok(bytes) if bytes.len() == 32 => Ok(key.to_lowercase()),
+                Ok(bytes) => Err(format!("initial public key must be 32 bytes long, but {} bytes are specified", bytes.len())),
+                Err(e) => Err(format!("initial public key must be a hex string: {}", e)),
+            }
+        }).transpose()?;
+        Ok(InitState { tvc: tvc.to_owned(), pubkey })
+    }
+}
+
+// loads state init from the tvc file to be attached to the call message and checks that it
+// corresponds to the contract address
+async fn prepare_state_init(init: &InitState, addr: &str, msg_params: &ParamsOfEncodeMessage) -> Result<DeploySet, String> {
+    let tvc_bytes = std::fs::read(&init.tvc)
+        .map_err(|e| format!("failed to read smart contract file: {}", e))?;
+    let wc = ton_block::MsgAddressInt::from_str(addr)
+        .map_err(|e| format!("couldn't decode address: {}", e))?
+        .workchain_id();
+    let pubkey = init.pubkey.clone().or_else(|| match &msg_params.signer {
+        Signer::Keys { keys } => Some(keys.public.clone()),
+        _ => None,
+    });
+    let calculated = calc_acc_address(&tvc_bytes, wc, pubkey.clone(), None, msg_params.abi.clone()).await?;
+    if calculated.to_lowercase() != addr.to_lowercase() {
+        return Err(format!("state init doesn't match the contract address: address calculated from the tvc is {}", calculated));
+    }
+    Ok(DeploySet {
+        tvc: base64::encode(&tvc_bytes),
+        workchain_id: Some(wc),
+        initial_pubkey: pubkey,
+        ..Default::default()
+    })
+}
+
+// Name of the call argument and the account data field used by contracts with seqno-based replay protection.
+const SEQNO_FIELD_NAME: &str = "seqno";
+
+// warns if "seqno" argument of the call is less than "seqno" field of the account data
+// decoded with the ABI, which means that the message will be rejected by the contract
+async fn warn_stale_seqno(ton: TonClient, addr: &str, abi: &Abi, params: &str, config: &Config) {
+    if config.is_json {
+        return;
+    }
+    let arg_seqno = serde_json::from_str::<Value>(params).ok()
+        .and_then(|params| params.get(SEQNO_FIELD_NAME).cloned())
+        .and_then(|seqno| json_to_u64(&seqno));
+    let arg_seqno = match arg_seqno {
+        Some(seqno) => seqno,
+        None => return,
+    };
+    let data = match query_account_field(ton.clone(), addr, "data").await {
+        Ok(data) => data,
+        Err(e) => {
+            println!("Warning: failed to check seqno, account data is not available: {}", e);
+            return;
+        }
+    };
+    let account_seqno = decode_account_data(
+        ton,
+        ParamsOfDecodeAccountData {
+            abi: abi.clone(),
+            data,
+            ..Default::default()
+        },
+    ).await.ok()
+        .and_then(|res| res.data.get(SEQNO_FIELD_NAME).and_then(json_to_u64));
+    match account_seqno {
+        Some(account_seqno) if arg_seqno < account_seqno => {
+            println!("Warning: \"{}\" argument {} is stale, current seqno of the account is {}.",
+                SEQNO_FIELD_NAME, arg_seqno, account_seqno);
+        },
+        Some(_) => {},
+        None => println!("Warning: failed to check seqno, \"{}\" field is not found in the account data.", SEQNO_FIELD_NAME),
+    }
+}
+
+fn json_to_u64(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) => match string.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => string.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+/// Post-processing hook for the decoded result of the call. It receives the method name and the
+/// decoded output and returns the transformed output.
+pub type ResultHook = dyn Fn(&str, Value) -> Result<Value, String> + Send + Sync;
+
+fn apply_result_hook(hook: Option<&ResultHook>, method: &str, result: Value) -> Result<Value, String> {
+    match hook {
+        Some(hook) => hook(method, result),
+        None => Ok(result),
+    }
+}
+
+/// Optional parameters of the call.
+#[derive(Default)]
+pub struct CallOptions<'a> {
+    /// Function header override, absent fields are computed as usual.
+    pub header: Option<FunctionHeader>,
+    /// Block time used for the local run.
+    pub now: Option<u32>,
+    /// State init attached to the call message to deploy the uninitialized account.
+    pub init_state: Option<InitState>,
+    /// Account balance used instead of the real one for the local run.
+    pub balance: Option<u64>,
+    /// Hook applied to the decoded result of the call.
+    pub hook: Option<&'a ResultHook>,
 }
 
 pub async fn call_contract_with_result(
@@ -256,7 +2140,9 @@ pub async fn call_contract_with_result(
     params: &str,
     keys: Option<String>,
     is_fee: bool,
+    options: CallOptions<'_>,
 ) -> Result<Value, String> {
+    check_method_exists(abi_path, method, config).await?;
     let ton = if config.debug_fail != "None".to_string() {
         let log_path = format!("call_{}_{}.log", addr, method);
         log::set_max_level(log::LevelFilter::Trace);
@@ -267,7 +2153,57 @@ pub async fn call_contract_with_result(
     } else {
         create_client_verbose(config)?
     };
-    call_contract_with_client(ton, config, addr, abi_path, method, params, keys, is_fee).await
+    call_contract_with_client(ton, config, addr, abi_path, method, params, keys, is_fee, options).await
+}
+
+// Expected code hash is either a hex string or a path to the tvc file.
+fn expected_code_hash(expected: &str) -> Result<String, String> {
+    let hash = expected.trim_start_matches("0x");
+    if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Ok(hash.to_lowercase());
+    }
+    let state = ton_block::StateInit::construct_from_file(expected)
+        .map_err(|e| format!("expected code hash is neither a hex string nor a tvc file: {}", e))?;
+    state.code.as_ref()
+        .map(|code| code.repr_hash().to_hex_string())
+        .ok_or_else(|| format!("tvc file {} doesn't contain code", expected))
+}
+
+/// Checks that the code hash of the deployed account matches the expected one (hex string or tvc file)
+/// to prevent calling a different contract at a reused address.
+pub async fn check_code_hash(config: &Config, addr: &str, expected: &str) -> Result<(), String> {
+    let expected = expected_code_hash(expected)?;
+    let ton = create_client(config)?;
+    let actual = query_account_field(ton, addr, "code_hash").await
+        .map_err(|e| format!("failed to check code hash: {}", e))?;
+    if actual != expected {
+        return Err(format!("code hash of the account {} is {}, but {} is expected", addr, actual, expected));
+    }
+    if !config.is_json {
+        println!("Code hash matches the expected one.");
+    }
+    Ok(())
+}
+
+/// Checks that the ABI contains the called function. It is done before the client is created,
+/// so that a typo in the method name fails without any network interaction.
+pub async fn check_method_exists(abi_path: &str, method: &str, config: &Config) -> Result<(), String> {
+    let abi = load_ton_abi(abi_path, config).await?;
+    find_function(&abi, method).map(|_| ())
+}
+
+// Rejects empty and all-zero destination addresses, which are usually placeholders left in templates.
+fn check_destination_address(addr: &str) -> Result<(), String> {
+    let address = ton_block::MsgAddressInt::from_str(addr)
+        .map_err(|e| format!("invalid destination address {}: {}", addr, e))?;
+    let address = address.to_string();
+    let is_zero = address.split_once(':')
+        .map(|(_, account_id)| account_id.chars().all(|c| c == '0'))
+        .unwrap_or(false);
+    if is_zero {
+        return Err(format!("destination address {} is a zero address, it is probably a placeholder", address));
+    }
+    Ok(())
 }
 
 pub async fn call_contract_with_client(
@@ -279,38 +2215,102 @@ pub async fn call_contract_with_client(
     params: &str,
     keys: Option<String>,
     is_fee: bool,
+    mut options: CallOptions<'_>,
 ) -> Result<Value, String> {
+    let hook = options.hook.take();
+    let result = try_call_contract_with_client(ton, config, addr, abi_path, method, params, keys, is_fee, options).await
+        .map_err(|e| e.to_string())?;
+    apply_result_hook(hook, method, result)
+}
+
+/// The same as `call_contract_with_client`, but SDK errors are returned as `ClientError`. The result
+/// hook of the options is not applied.
+pub async fn try_call_contract_with_client(
+    ton: TonClient,
+    config: &Config,
+    addr: &str,
+    abi_path: &str,
+    method: &str,
+    params: &str,
+    keys: Option<String>,
+    is_fee: bool,
+    options: CallOptions<'_>,
+) -> Result<Value, CallError> {
+    let CallOptions { header, now, init_state: tvc, balance, .. } = options;
+    check_destination_address(addr)?;
     let abi = load_abi(abi_path, config).await?;
+    warn_empty_params(&abi, method, params, config);
+    if config.check_storage_due && !config.is_json {
+        check_storage_due(ton.clone(), addr).await;
+    }
+    if config.check_frozen {
+        check_account_not_frozen(ton.clone(), addr).await?;
+    }
+    if config.replay_protection == "Seqno" {
+        warn_stale_seqno(ton.clone(), addr, &abi, params, config).await;
+    }
 
-    let msg_params = prepare_message_params(
+    let mut msg_params = prepare_message_params(
         addr,
         abi.clone(),
         method,
         params,
-        None,
+        header,
         keys.clone(),
     )?;
+    if let Some(tvc) = tvc {
+        msg_params.deploy_set = Some(prepare_state_init(&tvc, addr, &msg_params).await?);
+    }
+    if config.dump_encode_params && !config.is_json {
+        println!("Encode params: {:#}", encode_params_dump(&msg_params, abi_path)?);
+    }
 
     let needs_encoded_msg = is_fee ||
         config.async_call ||
+        config.wait_level == "Accepted" ||
         config.local_run ||
         config.debug_fail != "None".to_string();
 
-    let message = if needs_encoded_msg {
-        let msg = encode_message(ton.clone(), msg_params.clone()).await
-            .map_err(|e| format!("failed to create inbound message: {}", e))?;
+    let dump_encode = config.dump_encode_params && !config.is_json;
+    let message = if needs_encoded_msg || config.max_message_size != 0 || dump_encode {
+        let msg = match encode_message(ton.clone(), msg_params.clone()).await {
+            Ok(msg) => msg,
+            Err(e) => {
+                if dump_encode {
+                    dump_failed_encode(ton.clone(), &msg_params).await;
+                }
+                return Err(format!("failed to create inbound message: {}", e).into());
+            },
+        };
+        if dump_encode {
+            println!("Encoded message id: {}", msg.message_id);
+            println!("Encoded message boc: {}", msg.message);
+        }
+        check_message_size(&msg.message, config)?;
 
         if config.local_run || is_fee {
-            emulate_locally(ton.clone(), addr, msg.message.clone(), is_fee).await?;
+            let bc_config = fee_blockchain_config(config)?;
+            let value = if config.fees_percentage { attached_value(params) } else { None };
+            emulate_locally(ton.clone(), addr, msg.message.clone(), EmulateOptions {
+                is_fee,
+                allow_dummy: config.dummy_account,
+                now,
+                bc_config,
+                fees_on_abort: config.fees_on_abort,
+                balance,
+                src: config.emulate_src.as_deref(),
+                value,
+                unlimited_balance: config.unlimited_balance,
+            }).await?;
             if is_fee {
                 return Ok(Value::Null);
             }
         }
-        if config.async_call {
-            return send_message_and_wait(ton,
-                                         Some(abi),
-                                         msg.message.clone(),
-                                         config).await;
+        if config.async_call || config.wait_level == "Accepted" {
+            return try_send_message_and_wait(ton,
+                                             Some(abi),
+                                             msg.message.clone(),
+                                             config).await;
         }
         Some(msg.message)
     } else {
@@ -329,47 +2329,263 @@ pub async fn call_contract_with_client(
             .map_err(|e| format!("Failed to serialize account: {}", e))?;
 
         let now = now_ms();
-        Some((account, message.unwrap(), now, get_blockchain_config(config, None).await?))
+        let bc_config = get_blockchain_config(config, config.debug_bc_config.as_deref()).await?;
+        Some((account, message.unwrap(), now, bc_config))
+    } else {
+        None
+    };
+
+    let balance_before = if config.include_balance {
+        Some(query_account_balance(ton.clone(), addr).await)
     } else {
         None
     };
 
-    let res = process_message(ton.clone(), msg_params, config).await;
+    let res = process_message(ton.clone(), msg_params.clone(), config).await;
 
     if config.debug_fail != "None".to_string() && res.is_err()
         && res.clone().err().unwrap().code == SDK_EXECUTION_ERROR_CODE {
+        let (mut account, message, now, bc_config) = dump.unwrap();
+        let mut input = decode_call_parameters(ton.clone(), &message, abi.clone()).await
+            .map(|(method, parameters)| json!({ "Method": method, "Parameters": parameters }))
+            .unwrap_or_else(|e| Value::String(e));
+        redact_secrets(&mut input);
         if config.is_json {
             let e = format!("{:#}", res.clone().err().unwrap());
             let err: Value = serde_json::from_str(&e)
                 .unwrap_or(Value::String(e));
-            let res = json!({"Error": err});
+            let res = json!({"Error": err, "Input": input});
             println!("{}", serde_json::to_string_pretty(&res)
                 .unwrap_or("{{ \"JSON serialization error\" }}".to_string()));
         } else {
             println!("Error: {:#}", res.clone().err().unwrap());
+            println!("Failed input message: {:#}", input);
             println!("Execution failed. Starting debug...");
         }
-        let (mut account, message, now, bc_config) = dump.unwrap();
         let message = Message::construct_from_base64(&message)
             .map_err(|e| format!("failed to construct message: {}", e))?;
-        let _ = execute_debug(bc_config, &mut account, Some(&message), (now / 1000) as u32, now,now, false, config).await?;
+        if config.structured_trace {
+            let (result, trace) = execute_debug_with_trace(bc_config, &mut account, Some(&message), (now / 1000) as u32, now, now, false, config).await;
+            let trace_path = format!("call_{}_{}.trace.json", addr, method);
+            let trace = serde_json::to_string_pretty(&trace)
+                .map_err(|e| format!("failed to serialize trace: {}", e))?;
+            std::fs::write(&trace_path, trace)
+                .map_err(|e| format!("failed to write trace to file {}: {}", trace_path, e))?;
+            if !config.is_json {
+                println!("Trace saved to {}", trace_path);
+            }
+            let _ = result?;
+        } else {
+            let _ = execute_debug(bc_config, &mut account, Some(&message), (now / 1000) as u32, now,now, false, config).await?;
+        }
+
+        if !config.is_json {
+            let log_path = format!("call_{}_{}.log", addr, method);
+            println!("Debug finished.");
+            println!("Log saved to {}", log_path);
+        }
+        return Err(CallError::Other("".to_string()));
+    }
+    if let Err(e) = &res {
+        if is_insufficient_balance(e) {
+            return Err(insufficient_balance_error(ton, addr, msg_params, e, config).await.into());
+        }
+    }
+    let mut res = res?;
+    wait_for_masterchain_blocks(ton.clone(), config).await?;
+    if config.strict_output {
+        let abi = load_ton_abi(abi_path, config).await?;
+        check_output_params(&res, find_function(&abi, method)?)?;
+    }
+    if config.address_format != "None" {
+        let abi = load_ton_abi(abi_path, config).await?;
+        res = normalize_output_addresses(res, &find_function(&abi, method)?.outputs, config);
+    }
+    if let Some(before) = balance_before {
+        let after = query_account_balance(ton, addr).await;
+        if !res.is_object() {
+            res = json!({});
+        }
+        res["balance"] = json!({
+            "before": balance_json(before),
+            "after": balance_json(after),
+        });
+    }
+    Ok(res)
+}
+
+// Frozen account can't process messages, so sending to it only wastes fees.
+async fn check_account_not_frozen(ton: TonClient, addr: &str) -> Result<(), String> {
+    let acc_type = query_account_field(ton, addr, "acc_type_name").await
+        .map_err(|e| format!("failed to check account state: {}", e))?;
+    if acc_type == "Frozen" {
+        return Err(format!("account {} is frozen, it cannot process the message", addr));
+    }
+    Ok(())
+}
+
+// Storage due is accumulated when the account balance is not enough to pay storage fees. Such account is
+// frozen once the due exceeds the limit, which looks like a mysterious balance drop or call failure.
+async fn check_storage_due(ton: TonClient, addr: &str) {
+    let account = match query_account_field(ton, addr, "boc").await
+        .and_then(|boc| Account::construct_from_base64(&boc)
+            .map_err(|e| format!("failed to construct account: {}", e))) {
+        Ok(account) => account,
+        Err(e) => {
+            println!("Warning: failed to check storage due: {}", e);
+            return;
+        }
+    };
+    let storage = match account.storage_info() {
+        Some(storage) => storage,
+        None => return,
+    };
+    let due = storage.due_payment().map(|due| due.as_u128() as u64).unwrap_or(0);
+    let last_paid = storage.last_paid();
+    println!("Storage due: {} (last paid: {})", convert::convert_u64_to_tokens(due), last_paid);
+    if due != 0 {
+        println!("Warning: account {} owes {} tons of storage fees, they will be charged from the value of the call \
+            and the account may be frozen.", addr, convert::convert_u64_to_tokens(due));
+    }
+}
+
+// Checks whether the call failed because the account can't cover the fees. Processing errors keep
+// the error of the local execution of the message in the data.
+fn is_insufficient_balance(error: &ClientError) -> bool {
+    error.code == SDK_LOW_BALANCE_ERROR_CODE
+        || error.data["local_error"]["code"].as_u64() == Some(SDK_LOW_BALANCE_ERROR_CODE as u64)
+}
+
+// Estimates the balance required for the call by running the message locally with unlimited balance:
+// fees of the account plus the value of the outbound messages.
+async fn estimate_required_balance(ton: TonClient, addr: &str, msg_params: ParamsOfEncodeMessage, config: &Config) -> Option<u64> {
+    let msg = encode_message(ton.clone(), msg_params).await.ok()?;
+    let state = query_account_field(ton.clone(), addr, "boc").await.ok()?;
+    let bc_config = fee_blockchain_config(config).ok()?;
+    let res = execute_locally(ton, state, msg.message, Some(true), None, bc_config, Some(true)).await.ok()?;
+    Some(res.fees.total_account_fees + res.fees.total_output)
+}
+
+async fn insufficient_balance_error(
+    ton: TonClient,
+    addr: &str,
+    msg_params: ParamsOfEncodeMessage,
+    error: &ClientError,
+    config: &Config,
+) -> String {
+    let have = query_account_balance(ton.clone(), addr).await;
+    let need = estimate_required_balance(ton, addr, msg_params, config).await;
+    insufficient_balance_message(need, have, error)
+}
+
+fn insufficient_balance_message(need: Option<u64>, have: Option<u64>, error: &ClientError) -> String {
+    let tokens = |value: Option<u64>| value
+        .map(|value| format!("{} tons", convert::convert_u64_to_tokens(value)))
+        .unwrap_or("unknown".to_string());
+    format!("insufficient balance: need ~{}, have {}. Original error: {:#}", tokens(need), tokens(have), error)
+}
+
+// Balance is None if the account doesn't exist.
+async fn query_account_balance(ton: TonClient, addr: &str) -> Option<u64> {
+    let balance = query_account_field(ton, addr, "balance").await.ok()?;
+    match balance.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => u64::from_str(&balance).ok(),
+    }
+}
+
+// Account balance is always in nanotons, so it is converted to tokens with 9 decimals regardless of
+// `token_decimals` option.
+fn balance_json(balance: Option<u64>) -> Value {
+    match balance {
+        Some(balance) => json!({
+            "nanotons": balance.to_string(),
+            "tokens": convert::convert_u64_to_tokens(balance),
+        }),
+        None => Value::Null,
+    }
+}
+
+/// Calls the contract like `call_contract_with_result` and deserializes the decoded output
+/// of the function into `T`.
+pub async fn call_and_decode<T: DeserializeOwned>(
+    config: &Config,
+    addr: &str,
+    abi_path: &str,
+    method: &str,
+    params: &str,
+    keys: Option<String>,
+) -> Result<T, String> {
+    let result = call_contract_with_result(config, addr, abi_path, method, params, keys, false, CallOptions::default()).await?;
+    serde_json::from_value(result.clone())
+        .map_err(|e| format!(r#"failed to decode output of function "{}": {} (output: {})"#, method, e, result))
+}
 
-        if !config.is_json {
-            let log_path = format!("call_{}_{}.log", addr, method);
-            println!("Debug finished.");
-            println!("Log saved to {}", log_path);
-        }
-        return Err("".to_string());
+// Result is tabular if it is an array of objects or an object with a single field which is such array
+// (e.g. getter output `{"value0": [...]}`).
+fn table_rows(result: &Value) -> Option<&Vec<Value>> {
+    let rows = match result {
+        Value::Object(object) if object.len() == 1 => object.values().next()?.as_array()?,
+        Value::Array(rows) => rows,
+        _ => return None,
+    };
+    if rows.is_empty() || !rows.iter().all(|row| row.is_object()) {
+        return None;
     }
-    res.map_err(|e| format!("{:#}", e))
+    Some(rows)
+}
+
+/// Renders rows as a table with columns taken from the keys of the first row.
+fn render_table(rows: &[Value]) -> String {
+    let columns = rows[0].as_object().map(|row| row.keys().cloned().collect::<Vec<_>>()).unwrap_or_default();
+    let cells = rows.iter()
+        .map(|row| columns.iter()
+            .map(|column| match row.get(column) {
+                Some(Value::String(s)) => s.clone(),
+                Some(value) => value.to_string(),
+                None => String::new(),
+            })
+            .collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let widths = columns.iter().enumerate()
+        .map(|(i, column)| cells.iter().map(|row| row[i].len()).chain(std::iter::once(column.len())).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    let format_row = |row: &[String]| row.iter().zip(&widths)
+        .map(|(cell, width)| format!("{:width$}", cell, width = *width))
+        .collect::<Vec<_>>()
+        .join(" | ")
+        .trim_end()
+        .to_string();
+    let mut lines = vec![
+        format_row(&columns),
+        widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<_>>().join("-+-"),
+    ];
+    lines.extend(cells.iter().map(|row| format_row(row)));
+    lines.join("\n")
 }
 
 pub fn print_json_result(result: Value, config: &Config) -> Result<(), String> {
     if !result.is_null() {
-        let result = serde_json::to_string_pretty(&result)
-            .map_err(|e| format!("Failed to serialize the result: {}", e))?;
-        if !config.is_json {
-            println!("Result: {}", result);
+        let value = result;
+        let result = if config.canonical_json {
+            canonical_json(&value)
+        } else {
+            serde_json::to_string_pretty(&value)
+                .map_err(|e| format!("Failed to serialize the result: {}", e))?
+        };
+        if let Some(path) = &config.result_file {
+            std::fs::write(path, &result)
+                .map_err(|e| format!("Failed to write the result to file {}: {}", path, e))?;
+            if !config.is_json {
+                println!("Result saved to file: {}", path);
+            } else {
+                println!("{:#}", json!({ "ResultFile": path }));
+            }
+        } else if !config.is_json {
+            match table_rows(&value).filter(|_| config.table_output && !config.canonical_json) {
+                Some(rows) => println!("Result:\n{}", render_table(rows)),
+                None => println!("Result: {}", result),
+            }
         } else {
             println!("{}", result);
         }
@@ -377,6 +2593,89 @@ pub fn print_json_result(result: Value, config: &Config) -> Result<(), String> {
     Ok(())
 }
 
+/// Serializes the value to compact json with object keys sorted lexicographically (by bytes) on all
+/// levels, so equal values always produce the same output regardless of the key order in the map.
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(object) => {
+            let mut keys = object.keys().collect::<Vec<_>>();
+            keys.sort();
+            let fields = keys.into_iter()
+                .map(|key| format!("{}:{}", Value::String(key.clone()), canonical_json(&object[key])))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", fields.join(","))
+        },
+        Value::Array(array) => {
+            let items = array.iter().map(canonical_json).collect::<Vec<_>>();
+            format!("[{}]", items.join(","))
+        },
+        value => value.to_string(),
+    }
+}
+
+fn scalar_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+// Collects differences between the actual and the expected values. In subset mode fields absent in the expected
+// object are ignored. Scalars match if their string representations are equal (e.g. `1000` matches `"1000"`).
+fn collect_result_diff(path: &str, actual: &Value, expected: &Value, exact: bool, diff: &mut Vec<String>) {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => {
+            for (key, expected) in expected {
+                let path = format!("{}.{}", path, key);
+                match actual.get(key) {
+                    Some(actual) => collect_result_diff(&path, actual, expected, exact, diff),
+                    None => diff.push(format!("{}: expected {}, but field is missing", path, expected)),
+                }
+            }
+            if exact {
+                for (key, actual) in actual.iter().filter(|(key, _)| !expected.contains_key(*key)) {
+                    diff.push(format!("{}.{}: unexpected field with value {}", path, key, actual));
+                }
+            }
+        },
+        (Value::Array(actual), Value::Array(expected)) => {
+            if actual.len() != expected.len() {
+                diff.push(format!("{}: expected {} elements, got {}", path, expected.len(), actual.len()));
+                return;
+            }
+            for (i, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+                collect_result_diff(&format!("{}[{}]", path, i), actual, expected, exact, diff);
+            }
+        },
+        (Value::Object(_), _) | (Value::Array(_), _) | (_, Value::Object(_)) | (_, Value::Array(_)) => {
+            diff.push(format!("{}: expected {}, got {}", path, expected, actual));
+        },
+        _ => if scalar_to_string(actual) != scalar_to_string(expected) {
+            diff.push(format!("{}: expected {}, got {}", path, expected, actual));
+        },
+    }
+}
+
+/// Prints the call result and checks that it matches the expected value (the whole value in exact mode or
+/// the specified fields only in subset mode). Fails with the list of differences on mismatch.
+pub fn check_call_result(result: Value, expected: &str, exact: bool, config: &Config) -> Result<(), String> {
+    let expected: Value = serde_json::from_str(expected)
+        .map_err(|e| format!("expected result is not in json format: {}", e))?;
+    let mut diff = vec![];
+    collect_result_diff("result", &result, &expected, exact, &mut diff);
+    if !config.is_json {
+        println!("Succeeded.");
+    }
+    print_json_result(result, config)?;
+    if !diff.is_empty() {
+        return Err(format!("call result doesn't match the expected value:\n{}", diff.join("\n")));
+    }
+    if !config.is_json {
+        println!("Result matches the expected value.");
+    }
+    Ok(())
+}
+
 pub async fn call_contract(
     config: &Config,
     addr: &str,
@@ -385,17 +2684,75 @@ pub async fn call_contract(
     params: &str,
     keys: Option<String>,
     is_fee: bool,
+    options: CallOptions<'_>,
 ) -> Result<(), String> {
-    let result = call_contract_with_result(config, addr, abi_path, method, params, keys, is_fee).await?;
+    let echo = echoed_params(params, config);
+    let result = call_contract_with_result(config, addr, abi_path, method, params, keys, is_fee, options).await;
     if !config.is_json {
+        let result = result?;
+        if let Some(params) = &echo {
+            println!("Parameters: {:#}", params);
+        }
         println!("Succeeded.");
+        return print_json_result(result, config);
     }
-    print_json_result(result, config)?;
-    Ok(())
+    match result {
+        // fees are already printed by the local emulation
+        Ok(result) if is_fee => print_json_result(result, config),
+        Ok(result) => {
+            let output = with_echoed_params(json!({ "status": "ok", "result": result }), echo);
+            print_json_result(with_timestamp(output, config), config)
+        },
+        // error is already printed
+        Err(e) if e.is_empty() => Err(e),
+        Err(e) => {
+            let error: Value = serde_json::from_str(&e)
+                .unwrap_or(Value::String(e));
+            let output = with_echoed_params(json!({ "status": "error", "error": error }), echo);
+            println!("{:#}", with_timestamp(output, config));
+            Err("".to_string())
+        },
+    }
+}
+
+// Arguments of the call which are printed along with the result if `echo_params` config option is
+// enabled. Secret fields are redacted, because the output usually ends up in logs.
+fn echoed_params(params: &str, config: &Config) -> Option<Value> {
+    if !config.echo_params {
+        return None;
+    }
+    let mut params = serde_json::from_str(params).unwrap_or(Value::String(params.to_owned()));
+    redact_secrets(&mut params);
+    Some(params)
 }
 
+fn with_echoed_params(mut output: Value, params: Option<Value>) -> Value {
+    if let Some(params) = params {
+        output["params"] = params;
+    }
+    output
+}
 
-pub async fn call_contract_with_msg(config: &Config, str_msg: String, abi_path: &str) -> Result<(), String> {
+// adds UTC time of the call completion to the json result if `include_timestamp` config option is enabled
+fn with_timestamp(mut output: Value, config: &Config) -> Value {
+    if config.include_timestamp {
+        output["timestamp"] = json!(format_timestamp(chrono::Utc::now()));
+    }
+    output
+}
+
+fn format_timestamp(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+
+pub struct MsgCallResult {
+    pub method: String,
+    pub parameters: Value,
+    pub result: Value,
+}
+
+pub async fn call_contract_with_msg_result(config: &Config, str_msg: String, abi_path: &str) -> Result<MsgCallResult, String> {
     let ton = create_client_verbose(&config)?;
     let abi = load_abi(abi_path, config).await?;
 
@@ -403,20 +2760,187 @@ pub async fn call_contract_with_msg(config: &Config, str_msg: String, abi_path:
     if config.is_json {
         println!("{{");
     }
-    print_encoded_message(&msg, config.is_json);
+    print_encoded_message(&msg, config);
+
+    let result: Result<MsgCallResult, String> = async {
+        let (method, parameters) = decode_call_parameters(ton.clone(), &msg.message, abi.clone()).await?;
+        let params_str = serde_json::to_string_pretty(&printable_parameters(&parameters, abi_path, &method, config).await?)
+            .map_err(|e| format!("failed to serialize result: {}", e))?;
+
+        if !config.is_json {
+            println!("Calling method {} with parameters:", method);
+            println!("{}", params_str);
+            println!("Processing... ");
+        } else if config.include_timestamp {
+            // the object is completed with the call result and the time after the call
+            println!("{},", call_json_fields(&method, &params_str));
+        } else {
+            println!("{}", call_json_fields(&method, &params_str));
+            println!("}}");
+        }
+        let result = send_message_and_wait(ton, Some(abi), msg.message.clone(),  config).await?;
+        if config.strict_output && !config.async_call && config.wait_level != "Accepted" {
+            let abi = load_ton_abi(abi_path, config).await?;
+            check_output_params(&result, find_function(&abi, &method)?)?;
+        }
+
+        Ok(MsgCallResult { method, parameters, result })
+    }.await;
+
+    if config.is_json && config.include_timestamp {
+        // a single json document is printed: the result and the completion time are added to the object
+        // printed before sending the message (every field printed before ends with a comma)
+        let timestamp = Value::String(format_timestamp(chrono::Utc::now()));
+        match &result {
+            Ok(result) => println!("  \"Result\": {:#},\n  \"timestamp\": {}\n}}", result.result, timestamp),
+            Err(_) => println!("  \"timestamp\": {}\n}}", timestamp),
+        }
+    }
+    result
+}
 
-    let params = decode_call_parameters(ton.clone(), &msg, abi.clone()).await?;
+// Fields of the json object printed before sending the message. Method name is escaped and the
+// parameters are printed as serialized, so that unusual names are preserved exactly.
+fn call_json_fields(method: &str, params_str: &str) -> String {
+    format!("  \"Method\": {},\n  \"Parameters\": {}", Value::String(method.to_owned()), params_str)
+}
 
+/// Decodes the function call from the message without sending it. Message can be specified either
+/// as produced by `message` command or as base64 boc. Only the method and its parameters are printed.
+pub async fn decode_call_message(config: &Config, str_msg: &str, abi_path: &str) -> Result<(), String> {
+    let ton = create_client_local()?;
+    let abi = load_abi(abi_path, config).await?;
+    let message = match unpack_message(str_msg) {
+        Ok((msg, _)) => msg.message,
+        Err(_) => str_msg.to_owned(),
+    };
+    let (method, parameters) = decode_call_parameters(ton, &message, abi).await?;
+    let parameters = printable_parameters(&parameters, abi_path, &method, config).await?;
     if !config.is_json {
-        println!("Calling method {} with parameters:", params.0);
-        println!("{}", params.1);
-        println!("Processing... ");
+        println!("Method: {}", method);
+        println!("Parameters: {}", serde_json::to_string_pretty(&parameters)
+            .map_err(|e| format!("failed to serialize result: {}", e))?);
+    } else {
+        println!("{:#}", json!({ "Method": method, "Parameters": parameters }));
+    }
+    Ok(())
+}
+
+/// Sends all messages from the queue file filled by `message --queue` one by one and waits for
+/// their processing. Expired messages are skipped. Failure of one message doesn't stop the others.
+pub async fn send_queue(config: &Config, path: &str, abi_path: &str) -> Result<(), String> {
+    let queue = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read queue file {}: {}", path, e))?;
+    let cancelled = load_cancelled_ids(path)?;
+    let ton = create_client_verbose(&config)?;
+    let abi = load_abi(abi_path, config).await?;
+
+    let mut results = vec![];
+    let mut failed = 0;
+    let mut skipped = 0;
+    for (index, line) in queue.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let result = match unpack_json_message(line.trim()) {
+            Ok((msg, method)) if cancelled.contains(&msg.message_id) => {
+                if !config.is_json {
+                    println!("Skipping cancelled message {} ({}).", msg.message_id, method);
+                }
+                skipped += 1;
+                results.push(json!({ "MessageId": msg.message_id, "Method": method, "Skipped": "cancelled" }));
+                continue;
+            },
+            Ok((msg, method)) => {
+                if !config.is_json {
+                    println!("Sending message {} ({})...", msg.message_id, method);
+                }
+                let result = if msg.expire.map(|expire| expire <= now().unwrap_or(0)).unwrap_or(false) {
+                    Err("message is expired".to_string())
+                } else {
+                    send_message_and_wait(ton.clone(), Some(abi.clone()), msg.message, config).await
+                };
+                match result {
+                    Ok(result) => json!({ "MessageId": msg.message_id, "Method": method, "Result": result }),
+                    Err(e) => json!({ "MessageId": msg.message_id, "Method": method, "Error": e }),
+                }
+            },
+            Err(e) => json!({ "Line": index + 1, "Error": e }),
+        };
+        if result.get("Error").is_some() {
+            failed += 1;
+        }
+        if !config.is_json {
+            match result.get("Error") {
+                Some(error) => println!("Failed: {}", error),
+                None => println!("Succeeded."),
+            }
+        }
+        results.push(result);
+    }
+
+    if config.is_json {
+        println!("{:#}", json!(results));
+    } else if skipped != 0 {
+        println!("Sent {} of {} messages ({} cancelled).", results.len() - failed - skipped, results.len(), skipped);
+    } else {
+        println!("Sent {} of {} messages.", results.len() - failed, results.len());
+    }
+    if failed != 0 {
+        return Err(format!("{} of {} messages failed", failed, results.len()));
+    }
+    Ok(())
+}
+
+// Ids of the cancelled messages of the queue are stored in the file next to the queue file.
+fn cancelled_ids_path(queue: &str) -> String {
+    format!("{}.cancelled", queue)
+}
+
+fn load_cancelled_ids(queue: &str) -> Result<HashSet<String>, String> {
+    let path = cancelled_ids_path(queue);
+    match std::fs::read_to_string(&path) {
+        Ok(data) => Ok(data.lines()
+            .map(|line| line.trim().to_owned())
+            .filter(|line| !line.is_empty())
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(e) => Err(format!("failed to read cancelled messages file {}: {}", path, e)),
+    }
+}
+
+/// Marks the message from the queue as cancelled, so that `send_queue` skips it. This is local
+/// bookkeeping only: the message stays valid until it expires and can still be broadcast by anyone
+/// who has it.
+pub fn cancel_queued_message(config: &Config, queue: &str, message_id: &str) -> Result<(), String> {
+    let data = std::fs::read_to_string(queue)
+        .map_err(|e| format!("failed to read queue file {}: {}", queue, e))?;
+    let found = data.lines()
+        .filter_map(|line| unpack_json_message(line.trim()).ok())
+        .any(|(msg, _)| msg.message_id == message_id);
+    if !found {
+        return Err(format!("message {} is not found in the queue file {}", message_id, queue));
+    }
+    let already_cancelled = load_cancelled_ids(queue)?.contains(message_id);
+    if !already_cancelled {
+        let path = cancelled_ids_path(queue);
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)
+            .map_err(|e| format!("failed to open cancelled messages file {}: {}", path, e))?;
+        std::io::Write::write_all(&mut file, format!("{}\n", message_id).as_bytes())
+            .map_err(|e| format!("failed to write cancelled messages file {}: {}", path, e))?;
+    }
+    if !config.is_json {
+        if already_cancelled {
+            println!("Message {} is already cancelled.", message_id);
+        } else {
+            println!("Message {} is cancelled and will be skipped by send-queue command.", message_id);
+        }
+        println!("Warning: the message is still valid until it expires and can be broadcast by anyone who has it.");
     } else {
-        println!("  \"Method\": \"{}\",", params.0);
-        println!("  \"Parameters\": {},", params.1);
-        println!("}}");
+        println!("{:#}", json!({ "MessageId": message_id, "Cancelled": true }));
     }
-    let result = send_message_and_wait(ton, Some(abi), msg.message,  config).await?;
+    Ok(())
+}
+
+pub async fn call_contract_with_msg(config: &Config, str_msg: String, abi_path: &str) -> Result<(), String> {
+    let result = call_contract_with_msg_result(config, str_msg, abi_path).await?.result;
 
     if !config.is_json {
         println!("Succeeded.");
@@ -428,11 +2952,690 @@ pub async fn call_contract_with_msg(config: &Config, str_msg: String, abi_path:
     Ok(())
 }
 
+/// Converts relaxed JSON (unquoted keys, single-quoted strings, trailing commas, `//` and `/* */` comments)
+/// to strict JSON.
+fn normalize_relaxed_json(json: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                result.push('"');
+                loop {
+                    match chars.next() {
+                        Some('\\') => match chars.next() {
+                            Some('\'') => result.push('\''),
+                            Some(escaped) => { result.push('\\'); result.push(escaped); },
+                            None => return Err("unterminated string in arguments".to_string()),
+                        },
+                        Some(ch) if ch == c => break,
+                        Some('"') => result.push_str("\\\""),
+                        Some(ch) => result.push(ch),
+                        None => return Err("unterminated string in arguments".to_string()),
+                    }
+                }
+                result.push('"');
+            },
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next().map(|ch| ch != '\n').unwrap_or(false) {}
+            },
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if prev == '*' => break,
+                        Some(ch) => prev = ch,
+                        None => return Err("unterminated comment in arguments".to_string()),
+                    }
+                }
+            },
+            '}' | ']' => {
+                // drop trailing comma
+                let len = result.trim_end().len();
+                result.truncate(len);
+                if result.ends_with(',') {
+                    result.pop();
+                }
+                result.push(c);
+            },
+            c if c.is_ascii_digit() => {
+                // numbers are copied as is, including exponent
+                result.push(c);
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_ascii_alphanumeric() || ch == '.' || ch == '+' || ch == '-' {
+                        result.push(ch);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+            },
+            c if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+                let mut ident = c.to_string();
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_ascii_alphanumeric() || ch == '_' || ch == '$' {
+                        ident.push(ch);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                match ident.as_str() {
+                    "true" | "false" | "null" => result.push_str(&ident),
+                    _ => { result.push('"'); result.push_str(&ident); result.push('"'); },
+                }
+            },
+            c => result.push(c),
+        }
+    }
+    let value: Value = serde_json::from_str(&result)
+        .map_err(|e| format!("arguments are not a valid relaxed json: {}", e))?;
+    serde_json::to_string(&value).map_err(|e| format!("{}", e))
+}
+
+// Loads arguments from the file specified as `@path`. YAML and TOML files (detected by the extension)
+// are converted to json, other files are read as json.
+fn load_params_file(path: &str) -> Result<String, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to load params from file {}: {}", path, e))?;
+    let extension = std::path::Path::new(path).extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+    convert_params_text(&text, &extension)
+        .map_err(|e| format!("failed to parse params file {}: {}", path, e))
+}
+
+fn convert_params_text(text: &str, extension: &str) -> Result<String, String> {
+    let value: Value = match extension {
+        "yaml" | "yml" => serde_yaml::from_str(text)
+            .map_err(|e| format!("invalid yaml: {}", e))?,
+        "toml" => toml::from_str(text)
+            .map_err(|e| format!("invalid toml: {}", e))?,
+        _ => serde_json::from_str(text)
+            .map_err(|e| format!("invalid json: {}", e))?,
+    };
+    if !value.is_object() {
+        return Err("arguments must be a map of parameter names to values".to_string());
+    }
+    serde_json::to_string(&value).map_err(|e| format!("{}", e))
+}
+
 pub async fn parse_params(params_vec: Vec<&str>, abi_path: &str, method: &str, config: &Config) -> Result<String, String> {
-    if params_vec.len() == 1 {
+    if params_vec.len() == 1 && !is_name_value_param(params_vec[0]) {
         // if there is only 1 parameter it must be a json string with arguments
-        Ok(params_vec[0].to_owned())
+        if let Some(path) = params_vec[0].strip_prefix('@') {
+            load_params_file(path)
+        } else if config.relaxed_json {
+            normalize_relaxed_json(&load_params(params_vec[0])?)
+        } else {
+            Ok(params_vec[0].to_owned())
+        }
     } else {
         build_json_from_params(params_vec, abi_path, method, config).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // loads ABI with the given functions and common fields used by the tests
+    fn test_abi(functions: &str) -> ton_abi::Contract {
+        let abi = format!(r#"{{
+            "ABI version": 2, "version": "2.1", "header": ["time"],
+            "functions": {},
+            "data": [], "events": []
+        }}"#, functions);
+        ton_abi::Contract::load(abi.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_function_name() {
+        let abi = std::fs::read_to_string("tests/samples/wallet.abi.json").unwrap();
+        let abi = ton_abi::Contract::load(abi.as_bytes()).unwrap();
+        let function = abi.function("sendTransaction").unwrap();
+        let id = format!("0x{:08x}", function.get_input_id());
+        assert_eq!(resolve_function_name(&abi, &id).unwrap(), "sendTransaction");
+        assert_eq!(resolve_function_name(&abi, "sendTransaction").unwrap(), "sendTransaction");
+        assert!(resolve_function_name(&abi, "0x00000000").is_err());
+        assert!(resolve_function_name(&abi, "0xzz").is_err());
+
+        // functions are indexed in the order of the listing: constructor, fallback, sendTransaction
+        assert_eq!(resolve_function_name(&abi, "#1").unwrap(), "constructor");
+        assert_eq!(resolve_function_name(&abi, "#3").unwrap(), "sendTransaction");
+        assert!(resolve_function_name(&abi, "#0").is_err());
+        assert!(resolve_function_name(&abi, "#4").is_err());
+        assert!(resolve_function_name(&abi, "#x").is_err());
+    }
+
+    #[test]
+    fn test_encode_comment() {
+        let boc = base64::decode(encode_comment("hello").unwrap()).unwrap();
+        let cell = ton_types::cells_serialization::deserialize_tree_of_cells(&mut std::io::Cursor::new(&boc)).unwrap();
+        assert_eq!(cell.bit_length(), 32 + 5 * 8);
+        assert_eq!(&cell.data()[..9], b"\0\0\0\0hello");
+
+        assert!(encode_comment(&"a".repeat(MAX_COMMENT_BYTES)).is_ok());
+        assert!(encode_comment(&"a".repeat(MAX_COMMENT_BYTES + 1)).is_err());
+    }
+
+    #[test]
+    fn test_parse_array_param() {
+        let config = Config::default();
+        let kind = ParamType::Uint(32);
+        let expected = vec!["1".to_owned(), "2".to_owned(), "3".to_owned()];
+        assert_eq!(parse_array_param("arr", "1,2,3", &kind, &config).unwrap(), expected);
+        assert_eq!(parse_array_param("arr", "[1, 2, 3]", &kind, &config).unwrap(), expected);
+        assert_eq!(parse_array_param("arr", " [ 1 ,2,\t3 ] ", &kind, &config).unwrap(), expected);
+        assert_eq!(parse_array_param("arr", "[1,2,3,]", &kind, &config).unwrap(), expected);
+        assert_eq!(parse_array_param("arr", "[1, , 2,3, ]", &kind, &config).unwrap(), expected);
+        assert!(parse_array_param("arr", "[]", &kind, &config).unwrap().is_empty());
+        assert!(parse_array_param("arr", "[ , ]", &kind, &config).unwrap().is_empty());
+        assert_eq!(parse_array_param("arr", "[1T, 0.5T]", &kind, &config).unwrap(),
+            vec!["1000000000".to_owned(), "500000000".to_owned()]);
+    }
+
+    #[test]
+    fn test_annotate_params() {
+        let params = vec![
+            ton_abi::Param::new("dest", ParamType::Address),
+            ton_abi::Param::new("value", ParamType::Uint(128)),
+            ton_abi::Param::new("grams", ParamType::VarUint(16)),
+            ton_abi::Param::new("items", ParamType::Array(Box::new(ParamType::Tuple(vec![
+                ton_abi::Param::new("flag", ParamType::Bool),
+            ])))),
+        ];
+        let values = json!({
+            "dest": "0:1234",
+            "value": "1000",
+            "grams": "5",
+            "items": [{ "flag": true }],
+        });
+        let annotated = annotate_params(&values, &params);
+        assert_eq!(annotated["value"], json!({ "type": "uint128", "layout": "128 bits", "value": "1000" }));
+        assert_eq!(annotated["grams"]["layout"], "4-bit length in bytes, then up to 15 bytes");
+        assert_eq!(annotated["dest"]["value"], "0:1234");
+        assert_eq!(annotated["items"]["value"][0]["value"]["flag"],
+            json!({ "type": "bool", "layout": "1 bit", "value": true }));
+    }
+
+    #[test]
+    fn test_check_destination_address() {
+        assert!(check_destination_address("0:2bb4a0e8391e7ea8877f4825064924bd41ce110fce97e939d3323999e1efbb13").is_ok());
+        assert!(check_destination_address("-1:3333333333333333333333333333333333333333333333333333333333333333").is_ok());
+        assert!(check_destination_address("0:0000000000000000000000000000000000000000000000000000000000000000").is_err());
+        assert!(check_destination_address("-1:0000000000000000000000000000000000000000000000000000000000000000").is_err());
+        assert!(check_destination_address("").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_param() {
+        assert_eq!(parse_duration_param("90").unwrap(), "90");
+        assert_eq!(parse_duration_param("90s").unwrap(), "90");
+        assert_eq!(parse_duration_param("5m").unwrap(), "300");
+        assert_eq!(parse_duration_param("2h").unwrap(), "7200");
+        assert_eq!(parse_duration_param("\"1d\"").unwrap(), "86400");
+        assert!(parse_duration_param("1w").is_err());
+        assert!(parse_duration_param("h").is_err());
+        assert!(parse_duration_param("-1h").is_err());
+        assert!(is_name_value_param("period:duration=2h"));
+    }
+
+    #[test]
+    fn test_collect_result_diff() {
+        let actual = json!({ "value0": "1000", "owners": ["0x1", "0x2"], "flag": true, "extra": "x" });
+        let diff = |expected: Value, exact: bool| {
+            let mut diff = vec![];
+            collect_result_diff("result", &actual, &expected, exact, &mut diff);
+            diff
+        };
+        assert!(diff(json!({ "value0": 1000 }), false).is_empty());
+        assert!(diff(json!({ "flag": "true", "owners": ["0x1", "0x2"] }), false).is_empty());
+        assert_eq!(diff(json!({ "value0": "1001" }), false), vec![r#"result.value0: expected "1001", got "1000""#]);
+        assert_eq!(diff(json!({ "owners": ["0x1"] }), false), vec!["result.owners: expected 1 elements, got 2"]);
+        assert_eq!(diff(json!({ "missing": 1 }), false), vec!["result.missing: expected 1, but field is missing"]);
+
+        let expected = json!({ "value0": "1000", "owners": ["0x1", "0x2"], "flag": true });
+        assert!(diff(expected.clone(), false).is_empty());
+        assert_eq!(diff(expected, true), vec![r#"result.extra: unexpected field with value "x""#]);
+    }
+
+    #[test]
+    fn test_answer_id_param() {
+        let abi = test_abi(r#"[
+            {"name": "getValue", "inputs": [{"name": "answerId", "type": "uint32"}], "outputs": [{"name": "value0", "type": "uint256"}]},
+            {"name": "setValue", "inputs": [{"name": "answerId", "type": "uint32"}], "outputs": []},
+            {"name": "getOther", "inputs": [{"name": "id", "type": "uint32"}], "outputs": [{"name": "value0", "type": "uint256"}]}
+        ]"#);
+        assert_eq!(answer_id_param(abi.function("getValue").unwrap()), Some("answerId"));
+        assert_eq!(answer_id_param(abi.function("setValue").unwrap()), None);
+        assert_eq!(answer_id_param(abi.function("getOther").unwrap()), None);
+    }
+
+    #[test]
+    fn test_convert_raw_address() {
+        let account_id = "841288ed3b55d9cdafa806807f02a0ae0c169aa5edfe88a789a6482429756a94";
+        let original = format!("-1:{}", account_id);
+        assert_eq!(convert_raw_address(account_id, &original, &AddressStringFormat::AccountId), original);
+        let format = AddressStringFormat::Base64 { url: true, bounce: true, test: false };
+        assert_eq!(convert_raw_address("Ef8", &original, &format), "Ef8");
+    }
+
+    #[test]
+    fn test_expected_code_hash() {
+        let hash = "AB".repeat(32);
+        assert_eq!(expected_code_hash(&hash).unwrap(), "ab".repeat(32));
+        assert_eq!(expected_code_hash(&format!("0x{}", hash)).unwrap(), "ab".repeat(32));
+        assert!(expected_code_hash("not_existing.tvc").is_err());
+    }
+
+    #[test]
+    fn test_call_error() {
+        let error = CallError::from(interrupted_error(None));
+        match &error {
+            CallError::Client(e) => assert_eq!(e.code, WAIT_INTERRUPTED_CODE),
+            CallError::Other(_) => panic!("SDK error is expected"),
+        }
+        assert_eq!(error.to_string(), format!("{:#}", interrupted_error(None)));
+        assert_eq!(CallError::from("failed".to_string()).to_string(), "failed");
+    }
+
+    #[test]
+    fn test_batch_param() {
+        let abi = test_abi(r#"[
+            {"name": "batch", "inputs": [{"name": "calls", "type": "cell[]"}], "outputs": []},
+            {"name": "other", "inputs": [{"name": "calls", "type": "cell[]"}, {"name": "flag", "type": "bool"}], "outputs": []}
+        ]"#);
+        assert_eq!(batch_param(abi.function("batch").unwrap()).unwrap(), "calls");
+        assert!(batch_param(abi.function("other").unwrap()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_nullary_params() {
+        let config = Config::default();
+        let abi = "tests/samples/wallet.abi.json";
+        assert_eq!(parse_params(vec![], abi, "constructor", &config).await.unwrap(), "{}");
+        assert_eq!(parse_params(vec!["{}"], abi, "constructor", &config).await.unwrap(), "{}");
+        assert!(parse_params(vec!["--a", "1"], abi, "constructor", &config).await.is_err());
+
+        let path = std::env::temp_dir().join("tonos_cli_test_nullary_params.json");
+        std::fs::write(&path, "{}").unwrap();
+        let file_arg = format!("@{}", path.to_str().unwrap());
+        assert_eq!(parse_params(vec![&file_arg], abi, "constructor", &config).await.unwrap(), "{}");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_render_table() {
+        let result = json!({ "value0": [
+            { "addr": "0:12", "value": "1000" },
+            { "addr": "0:3456", "value": 5 },
+        ]});
+        let rows = table_rows(&result).unwrap();
+        assert_eq!(render_table(rows), "addr   | value\n-------+------\n0:12   | 1000\n0:3456 | 5");
+
+        assert!(table_rows(&json!({ "a": [], "b": 1 })).is_none());
+        assert!(table_rows(&json!({ "a": [] })).is_none());
+        assert!(table_rows(&json!([1, 2])).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_check_method_exists() {
+        let config = Config::default();
+        let abi = "tests/samples/wallet.abi.json";
+        assert!(check_method_exists(abi, "sendTransaction", &config).await.is_ok());
+        assert!(check_method_exists(abi, "sendTransactoin", &config).await.is_err());
+    }
+
+    #[test]
+    fn test_check_output_params() {
+        let abi = test_abi(r#"[
+            {"name": "get", "inputs": [], "outputs": [{"name": "value0", "type": "uint256"}]}
+        ]"#);
+        let function = abi.function("get").unwrap();
+        assert!(check_output_params(&json!({ "value0": "1" }), function).is_ok());
+        assert!(check_output_params(&json!({ "value0": "1", "events": [] }), function).is_ok());
+        assert!(check_output_params(&json!({}), function).is_err());
+        assert!(check_output_params(&json!({ "value0": "1", "other": 2 }), function).is_err());
+    }
+
+    #[test]
+    fn test_unusual_output_names() {
+        let abi = test_abi(r#"[
+            {"name": "get", "inputs": [], "outputs": [
+                {"name": "_answer_id", "type": "uint32"},
+                {"name": "events", "type": "uint8"}
+            ]}
+        ]"#);
+        let function = abi.function("get").unwrap();
+
+        // extra fields don't overwrite outputs with the same name
+        let decoded = json!({ "_answer_id": "1", "events": "2" });
+        let mut output = decoded.as_object().unwrap().clone();
+        let name = extra_field_name(&output, "events");
+        assert_eq!(name, "events_");
+        output.insert(name, json!([]));
+        let output = Value::Object(output);
+        assert!(check_output_params(&output, function).is_ok());
+        assert_eq!(declared_output(&output, &decoded), decoded);
+        assert_eq!(declared_output(&json!({ "value0": "1", "events": [] }), &json!({ "value0": "1" })), json!({ "value0": "1" }));
+
+        // names are preserved exactly in the printed json
+        let parameters = json!({ "_answer_id": "1", "a.b": "2", "quo\"te": "3", "": "4" });
+        let params_str = serde_json::to_string_pretty(&parameters).unwrap();
+        let printed: Value = serde_json::from_str(&format!("{{\n{}\n}}", call_json_fields("get.\"x\"", &params_str))).unwrap();
+        assert_eq!(printed["Method"], json!("get.\"x\""));
+        assert_eq!(printed["Parameters"], parameters);
+        assert_eq!(canonical_json(&parameters), r#"{"":"4","_answer_id":"1","a.b":"2","quo\"te":"3"}"#);
+    }
+
+    #[test]
+    fn test_init_state() {
+        let key = "AB".repeat(32);
+        let init = InitState::new("contract.tvc", Some(&format!("0x{}", key))).unwrap();
+        assert_eq!(init.pubkey.unwrap(), "ab".repeat(32));
+        assert!(InitState::new("contract.tvc", None).unwrap().pubkey.is_none());
+        assert!(InitState::new("contract.tvc", Some("abcd")).is_err());
+        assert!(InitState::new("contract.tvc", Some(&"zz".repeat(32))).is_err());
+    }
+
+    #[test]
+    fn test_parse_scientific_integer() {
+        assert_eq!(parse_integer_param("1e9", 9).unwrap(), "1000000000");
+        assert_eq!(parse_integer_param("1.5e18", 9).unwrap(), "1500000000000000000");
+        assert_eq!(parse_integer_param("-2.5E+3", 9).unwrap(), "-2500");
+        assert_eq!(parse_integer_param("1500e-2", 9).unwrap(), "15");
+        assert!(parse_integer_param("1.5e0", 9).is_err());
+        assert!(parse_integer_param("1e-1", 9).is_err());
+        assert!(parse_integer_param("1e1000", 9).is_err());
+        assert_eq!(parse_integer_param("1.5e0T", 9).unwrap(), "1500000000");
+        assert_eq!(parse_integer_param("2e3T", 9).unwrap(), "2000000000000");
+        assert_eq!(parse_integer_param("1.5T", 6).unwrap(), "1500000");
+        assert_eq!(parse_integer_param("1000T", 18).unwrap(), "1000000000000000000000");
+        assert!(parse_integer_param("1.0000001T", 6).is_err());
+        // hex and plain values are not affected
+        assert_eq!(parse_integer_param("0x1e5", 9).unwrap(), "0x1e5");
+        assert_eq!(parse_integer_param("100", 9).unwrap(), "100");
+    }
+
+    #[test]
+    fn test_redact_secrets() {
+        let mut value = json!({
+            "signer": { "type": "Keys", "keys": { "public": "01", "secret": "02" } },
+            "input": [{ "privateKey": "03", "signature": "04", "seedPhrase": "word word" }, "05"],
+            "secret_none": null,
+        });
+        redact_secrets(&mut value);
+        assert_eq!(value, json!({
+            "signer": { "type": "Keys", "keys": { "public": "01", "secret": "<redacted>" } },
+            "input": [{ "privateKey": "<redacted>", "signature": "<redacted>", "seedPhrase": "<redacted>" }, "05"],
+            "secret_none": null,
+        }));
+    }
+
+    #[test]
+    fn test_encode_params_dump_has_no_secret() {
+        let keys: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string("tests/samples/test.keys.json").unwrap()
+        ).unwrap();
+        let secret = keys["secret"].as_str().unwrap();
+        let abi = std::fs::read_to_string("tests/samples/wallet.abi.json").unwrap();
+        let params = format!(r#"{{"dest":"0:{}","value":1,"bounce":false,"secret":"{}"}}"#, "1".repeat(64), secret);
+        let msg_params = prepare_message_params(
+            &format!("0:{}", "1".repeat(64)),
+            Abi::Json(abi),
+            "sendTransaction",
+            &params,
+            None,
+            Some("tests/samples/test.keys.json".to_owned()),
+        ).unwrap();
+        let dump = encode_params_dump(&msg_params, "tests/samples/wallet.abi.json").unwrap();
+        let dump = format!("{:#}", dump);
+        assert!(!dump.contains(secret));
+        assert!(dump.contains(keys["public"].as_str().unwrap()));
+        assert!(dump.contains("tests/samples/wallet.abi.json"));
+    }
+
+    #[test]
+    fn test_convert_params_text() {
+        let expected = json!({ "dest": "0:01", "value": 1000000000, "bounce": false, "ids": [1, 2] });
+        let yaml = "dest: \"0:01\"\nvalue: 1000000000\nbounce: false\nids:\n  - 1\n  - 2\n";
+        let json: Value = serde_json::from_str(&convert_params_text(yaml, "yaml").unwrap()).unwrap();
+        assert_eq!(json, expected);
+        let toml = "dest = \"0:01\"\nvalue = 1000000000\nbounce = false\nids = [1, 2]\n";
+        let json: Value = serde_json::from_str(&convert_params_text(toml, "toml").unwrap()).unwrap();
+        assert_eq!(json, expected);
+
+        let err = convert_params_text("dest: [", "yml").unwrap_err();
+        assert!(err.starts_with("invalid yaml"));
+        let err = convert_params_text("dest = ", "toml").unwrap_err();
+        assert!(err.starts_with("invalid toml"));
+        assert!(convert_params_text("- 1\n- 2\n", "yaml").is_err());
+    }
+
+    #[test]
+    fn test_expire_at() {
+        let future = now().unwrap() + 3600;
+        assert_eq!(parse_expire_at(&future.to_string()).unwrap(), future);
+        assert!(parse_expire_at("1").is_err());
+        assert!(parse_expire_at("soon").is_err());
+
+        assert_eq!(apply_expire_at(None, future).unwrap().expire, Some(future));
+        let header = parse_header_override(r#"{"time":1650000000000}"#).unwrap();
+        let header = apply_expire_at(Some(header), future).unwrap();
+        assert_eq!(header.time, Some(1650000000000));
+        assert_eq!(header.expire, Some(future));
+        let header = parse_header_override(r#"{"expire":1650000060}"#).unwrap();
+        assert!(apply_expire_at(Some(header), future).is_err());
+    }
+
+    #[test]
+    fn test_apply_result_hook() {
+        let result = json!({ "value0": "1" });
+        assert_eq!(apply_result_hook(None, "getValue", result.clone()).unwrap(), result);
+
+        let hook = |method: &str, mut result: Value| {
+            result["method"] = json!(method);
+            Ok(result)
+        };
+        assert_eq!(
+            apply_result_hook(Some(&hook), "getValue", result.clone()).unwrap(),
+            json!({ "value0": "1", "method": "getValue" })
+        );
+        let failing = |_: &str, _: Value| -> Result<Value, String> { Err("rejected".to_string()) };
+        assert_eq!(apply_result_hook(Some(&failing), "getValue", result).unwrap_err(), "rejected");
+    }
+
+    #[test]
+    fn test_insufficient_balance() {
+        let error = ClientError { code: SDK_LOW_BALANCE_ERROR_CODE, message: "low balance".to_string(), data: json!({}) };
+        assert!(is_insufficient_balance(&error));
+        let error = ClientError {
+            code: 507,
+            message: "message expired".to_string(),
+            data: json!({ "local_error": { "code": SDK_LOW_BALANCE_ERROR_CODE } }),
+        };
+        assert!(is_insufficient_balance(&error));
+        let error = ClientError { code: SDK_EXECUTION_ERROR_CODE, message: "exit code 100".to_string(), data: json!({}) };
+        assert!(!is_insufficient_balance(&error));
+
+        let message = insufficient_balance_message(Some(1_500_000_000), Some(100_000_000), &error);
+        assert!(message.starts_with("insufficient balance: need ~1.5"));
+        assert!(message.contains("have 0.1"));
+        assert!(insufficient_balance_message(None, None, &error).contains("need ~unknown, have unknown"));
+    }
+
+    #[test]
+    fn test_emulate_locally_offline() {
+        let address = "0:1111111111111111111111111111111111111111111111111111111111111111";
+        let account = dummy_account_boc(address).unwrap();
+        let dst = ton_block::MsgAddressInt::from_str(address).unwrap();
+        let src = ton_block::MsgAddressInt::from_str(
+            "0:2222222222222222222222222222222222222222222222222222222222222222").unwrap();
+        let message = Message::with_int_header(ton_block::InternalMessageHeader::with_addresses_and_bounce(
+            src, dst, CurrencyCollection::with_grams(1_000_000_000), false));
+        let message = tree_of_cells_into_base64(Some(&message.serialize().unwrap())).unwrap();
+
+        let (_, fees) = emulate_locally_offline(&account, &message, BlockchainConfig::default(),
+            Some(1650000000), None).unwrap();
+        assert_eq!(fees.fees.total_output, 0);
+        assert_eq!(fees.balance_override, None);
+
+        let (_, fees) = emulate_locally_offline(&account, &message, BlockchainConfig::default(),
+            Some(1650000000), Some(500)).unwrap();
+        assert_eq!(fees.balance_override, Some(500));
+        assert!(emulate_locally_offline(&account, "notaboc", BlockchainConfig::default(), None, None).is_err());
+    }
+
+    #[test]
+    fn test_fees_percent_of_value() {
+        assert_eq!(attached_value(r#"{"dest":"0:01","value":"1000000000"}"#), Some(1000000000));
+        assert_eq!(attached_value(r#"{"value":"0x3b9aca00"}"#), Some(1000000000));
+        assert_eq!(attached_value(r#"{"value":500}"#), Some(500));
+        assert_eq!(attached_value(r#"{"dest":"0:01"}"#), None);
+
+        let fees = TransactionFees {
+            gas_fee: 10_000_000,
+            total_account_fees: 12_500_000,
+            ..Default::default()
+        };
+        let percent = fees_percent_of_value(&fees, 1_000_000_000);
+        assert_eq!(percent["gas_fee"], json!("1.00%"));
+        assert_eq!(percent["total_account_fees"], json!("1.25%"));
+        assert_eq!(percent["storage_fee"], json!("0.00%"));
+    }
+
+    #[test]
+    fn test_parse_optional_param() {
+        let config = Config::default();
+        let kind = ParamType::Optional(Box::new(ParamType::Uint(32)));
+        assert_eq!(parse_typed_param("a", &kind, "", false, &config).unwrap(), Value::Null);
+        assert_eq!(parse_typed_param("a", &kind, "null", false, &config).unwrap(), Value::Null);
+        assert_eq!(parse_typed_param("a", &kind, "0x10", false, &config).unwrap(), json!("0x10"));
+        assert_eq!(parse_typed_param("a", &kind, "1e2", false, &config).unwrap(), json!("100"));
+        assert_eq!(parse_typed_param("a", &kind, "1m", true, &config).unwrap(), json!("60"));
+
+        let kind = ParamType::Optional(Box::new(ParamType::Array(Box::new(ParamType::Uint(8)))));
+        assert_eq!(parse_typed_param("a", &kind, "[1,2]", false, &config).unwrap(), json!(["1", "2"]));
+
+        let params = vec![ton_abi::Param::new("a", ParamType::Optional(Box::new(ParamType::Uint(32))))];
+        let tokens = ton_abi::token::Tokenizer::tokenize_all_params(&params, &json!({ "a": null })).unwrap();
+        assert!(matches!(tokens[0].value, ton_abi::TokenValue::Optional(_, None)));
+        let tokens = ton_abi::token::Tokenizer::tokenize_all_params(&params, &json!({ "a": "16" })).unwrap();
+        assert!(matches!(tokens[0].value, ton_abi::TokenValue::Optional(_, Some(_))));
+    }
+
+    #[test]
+    fn test_echoed_params() {
+        let mut config = Config::default();
+        assert_eq!(echoed_params(r#"{"dest":"0:01"}"#, &config), None);
+        config.echo_params = true;
+        let params = echoed_params(r#"{"dest":"0:01","secret":"abcd"}"#, &config).unwrap();
+        assert_eq!(params["dest"], json!("0:01"));
+        assert_eq!(params["secret"], json!(REDACTED_VALUE));
+        let output = with_echoed_params(json!({ "status": "ok", "result": {} }), Some(params));
+        assert_eq!(output["params"]["dest"], json!("0:01"));
+        assert_eq!(with_echoed_params(json!({ "status": "ok" }), None), json!({ "status": "ok" }));
+    }
+
+    #[test]
+    fn test_canonical_json() {
+        let value = json!({ "b": [1, { "z": null, "a": "x\"y" }], "a": { "d": true, "c": 1.5 } });
+        assert_eq!(canonical_json(&value), r#"{"a":{"c":1.5,"d":true},"b":[1,{"a":"x\"y","z":null}]}"#);
+        assert_eq!(canonical_json(&json!({})), "{}");
+        assert_eq!(canonical_json(&json!("str")), r#""str""#);
+    }
+
+    #[test]
+    fn test_with_timestamp() {
+        let mut config = Config::default();
+        assert_eq!(with_timestamp(json!({ "status": "ok" }), &config), json!({ "status": "ok" }));
+        config.include_timestamp = true;
+        let output = with_timestamp(json!({ "status": "ok" }), &config);
+        assert!(output["timestamp"].as_str().unwrap().ends_with('Z'));
+        let time = chrono::TimeZone::timestamp_opt(&chrono::Utc, 1650000000, 123_000_000).unwrap();
+        assert_eq!(format_timestamp(time), "2022-04-15T05:20:00.123Z");
+    }
+
+    #[test]
+    fn test_find_param_value() {
+        let params = vec!["--dest", "0:1234", "value=1000", "-bounce", "true", "payload=te=st"];
+        assert_eq!(find_param_value(&params, "dest"), Some(Some("0:1234".to_owned())));
+        assert_eq!(find_param_value(&params, "value"), Some(Some("1000".to_owned())));
+        assert_eq!(find_param_value(&params, "bounce"), Some(Some("true".to_owned())));
+        assert_eq!(find_param_value(&params, "payload"), Some(Some("te=st".to_owned())));
+        assert_eq!(find_param_value(&params, "flags"), None);
+
+        // value of the dash-prefixed argument is not treated as name=value token
+        let params = vec!["--comment", "dest=0:1234"];
+        assert_eq!(find_param_value(&params, "dest"), None);
+        assert_eq!(find_param_value(&params, "comment"), Some(Some("dest=0:1234".to_owned())));
+
+        let params = vec!["--dest"];
+        assert_eq!(find_param_value(&params, "dest"), Some(None));
+
+        let params = vec!["--amount", "1", "--dest", "0:1234", "amount=2"];
+        assert_eq!(find_param_values(&params, "amount"), vec![Some("1".to_owned()), Some("2".to_owned())]);
+        assert!(check_duplicate_param(&params, "amount").unwrap_err().contains("specified 2 times (values: 1, 2)"));
+        assert!(check_duplicate_param(&params, "dest").is_ok());
+        let params = vec!["--period", "10", "--period:duration", "1h"];
+        assert!(check_duplicate_param(&params, "period").is_err());
+
+        assert!(is_name_value_param("dest=0:1234"));
+        assert!(!is_name_value_param(r#"{"dest":"a=b"}"#));
+        assert!(!is_name_value_param("./params=1.json"));
+    }
+
+    #[test]
+    fn test_display_signed_outputs() {
+        let params = vec![
+            ton_abi::Param::new("a", ParamType::Int(256)),
+            ton_abi::Param::new("b", ParamType::Int(8)),
+            ton_abi::Param::new("c", ParamType::Uint(8)),
+            ton_abi::Param::new("d", ParamType::Array(Box::new(ParamType::Int(8)))),
+        ];
+        let output = json!({
+            "a": format!("0x{}", "f".repeat(64)),
+            "b": "127",
+            "c": "255",
+            "d": ["128", "-5", "0x7f"],
+        });
+        assert_eq!(display_signed_outputs(output, &params), json!({
+            "a": "-1",
+            "b": "127",
+            "c": "255",
+            "d": ["-128", "-5", "0x7f"],
+        }));
+
+        let max = (BigInt::from(1) << 256) - 1;
+        let output = json!({ "a": max.to_string() });
+        assert_eq!(display_signed_outputs(output, &params), json!({ "a": "-1" }));
+        let min = BigInt::from(1) << 255;
+        let output = json!({ "a": format!("0x{}", min.to_str_radix(16)) });
+        assert_eq!(display_signed_outputs(output, &params), json!({ "a": format!("-{}", min) }));
+    }
+
+    #[test]
+    fn test_normalize_relaxed_json() {
+        let json = r#"{
+            // destination
+            dest: '0:1234', /* amount in nanotons */ value: 1e9,
+            comment: 'it\'s "ok"',
+            flags: [1, 2, 3,],
+            bounce: false,
+        }"#;
+        let json: Value = serde_json::from_str(&normalize_relaxed_json(json).unwrap()).unwrap();
+        assert_eq!(json, json!({
+            "dest": "0:1234",
+            "value": 1e9,
+            "comment": "it's \"ok\"",
+            "flags": [1, 2, 3],
+            "bounce": false,
+        }));
+        assert_eq!(normalize_relaxed_json(r#"{"a": "b"}"#).unwrap(), r#"{"a":"b"}"#);
+        assert!(normalize_relaxed_json("{a: 'b}").is_err());
+        assert!(normalize_relaxed_json("{a: /* b }").is_err());
+    }
+}
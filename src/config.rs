@@ -16,7 +16,7 @@ use clap::ArgMatches;
 use lazy_static::lazy_static;
 use regex::Regex;
 use crate::global_config_path;
-use crate::helpers::default_config_name;
+use crate::helpers::{default_config_name, parse_ext_address, synthetic_blockchain_config};
 
 const TESTNET: &str = "net.evercloud.dev";
 const MAINNET: &str = "main.evercloud.dev";
@@ -62,12 +62,33 @@ fn default_aliases() -> BTreeMap<String, ContractData> {
     BTreeMap::new()
 }
 
+fn default_networks() -> BTreeMap<String, NetworkProfile> {
+    BTreeMap::new()
+}
+
 fn default_endpoints_map() -> BTreeMap<String, Vec<String>> {
     FullConfig::default_map()
 }
 
 fn default_trace() -> String { "None".to_string() }
 
+fn default_wait_blocks() -> u32 { 0 }
+
+fn default_tree_depth() -> u32 { 0 }
+
+// message size is not checked by default, because the check requires an additional encoding of the message
+fn default_max_message_size() -> u32 { 0 }
+
+fn default_replay_protection() -> String { "None".to_string() }
+
+fn default_poll_interval_ms() -> u32 { 0 }
+
+fn default_address_format() -> String { "None".to_string() }
+
+fn default_wait_level() -> String { "Processed".to_string() }
+
+fn default_token_decimals() -> u32 { 9 }
+
 fn default_config() -> Config {
     Config::new()
 }
@@ -109,6 +130,68 @@ pub struct Config {
     pub async_call: bool,
     #[serde(default = "default_trace")]
     pub debug_fail: String,
+    #[serde(default = "default_wait_blocks")]
+    pub wait_blocks: u32,
+    #[serde(default = "default_false")]
+    pub dummy_account: bool,
+    pub result_file: Option<String>,
+    #[serde(default = "default_false")]
+    pub include_boc: bool,
+    #[serde(default = "default_false")]
+    pub check_int_range: bool,
+    #[serde(default = "default_false")]
+    pub fastest_endpoint: bool,
+    #[serde(default = "default_tree_depth")]
+    pub tree_depth: u32,
+    #[serde(default = "default_max_message_size")]
+    pub max_message_size: u32,
+    pub fee_config: Option<String>,
+    #[serde(default = "default_replay_protection")]
+    pub replay_protection: String,
+    pub metrics_file: Option<String>,
+    #[serde(default = "default_false")]
+    pub relaxed_json: bool,
+    #[serde(default = "default_false")]
+    pub fees_on_abort: bool,
+    #[serde(default = "default_false")]
+    pub include_balance: bool,
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u32,
+    #[serde(default = "default_false")]
+    pub decode_layout: bool,
+    pub config_address: Option<String>,
+    #[serde(default = "default_address_format")]
+    pub address_format: String,
+    #[serde(default = "default_false")]
+    pub utc_time: bool,
+    #[serde(default = "default_false")]
+    pub check_storage_due: bool,
+    #[serde(default = "default_false")]
+    pub table_output: bool,
+    #[serde(default = "default_false")]
+    pub strict_output: bool,
+    #[serde(default = "default_false")]
+    pub structured_trace: bool,
+    #[serde(default = "default_false")]
+    pub check_frozen: bool,
+    pub emulate_src: Option<String>,
+    #[serde(default = "default_false")]
+    pub dump_encode_params: bool,
+    #[serde(default = "default_wait_level")]
+    pub wait_level: String,
+    #[serde(default = "default_false")]
+    pub fees_percentage: bool,
+    pub debug_bc_config: Option<String>,
+    #[serde(default = "default_token_decimals")]
+    pub token_decimals: u32,
+    #[serde(default = "default_false")]
+    pub echo_params: bool,
+    #[serde(default = "default_false")]
+    pub unlimited_balance: bool,
+    #[serde(default = "default_false")]
+    pub canonical_json: bool,
+    #[serde(default = "default_false")]
+    pub include_timestamp: bool,
 
     // SDK authentication parameters
     pub project_id: Option<String>,
@@ -126,6 +209,14 @@ pub struct ContractData {
     pub key_path: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NetworkProfile {
+    pub url: String,
+    #[serde(default = "default_endpoints")]
+    pub endpoints: Vec<String>,
+    pub config_address: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct FullConfig {
     #[serde(default = "default_config")]
@@ -134,6 +225,8 @@ pub struct FullConfig {
     pub endpoints_map: BTreeMap<String, Vec<String>>,
     #[serde(default = "default_aliases")]
     pub aliases: BTreeMap<String, ContractData>,
+    #[serde(default = "default_networks")]
+    pub networks: BTreeMap<String, NetworkProfile>,
     #[serde(default = "default_config_name")]
     pub path: String,
 }
@@ -163,6 +256,40 @@ impl Default for Config {
             endpoints: default_endpoints(),
             out_of_sync_threshold: default_out_of_sync(),
             debug_fail: default_trace(),
+            wait_blocks: default_wait_blocks(),
+            dummy_account: default_false(),
+            result_file: None,
+            include_boc: default_false(),
+            check_int_range: default_false(),
+            fastest_endpoint: default_false(),
+            tree_depth: default_tree_depth(),
+            max_message_size: default_max_message_size(),
+            fee_config: None,
+            replay_protection: default_replay_protection(),
+            metrics_file: None,
+            relaxed_json: default_false(),
+            fees_on_abort: default_false(),
+            include_balance: default_false(),
+            poll_interval_ms: default_poll_interval_ms(),
+            decode_layout: default_false(),
+            config_address: None,
+            address_format: default_address_format(),
+            utc_time: default_false(),
+            check_storage_due: default_false(),
+            table_output: default_false(),
+            strict_output: default_false(),
+            structured_trace: default_false(),
+            check_frozen: default_false(),
+            emulate_src: None,
+            dump_encode_params: default_false(),
+            wait_level: default_wait_level(),
+            fees_percentage: default_false(),
+            debug_bc_config: None,
+            token_decimals: default_token_decimals(),
+            echo_params: default_false(),
+            unlimited_balance: default_false(),
+            canonical_json: default_false(),
+            include_timestamp: default_false(),
             project_id: None,
             access_key: None,
         }
@@ -175,6 +302,7 @@ impl Default for FullConfig {
             config: default_config(),
             endpoints_map: default_endpoints_map(),
             aliases: default_aliases(),
+            networks: default_networks(),
             path: default_config_name(),
         }
     }
@@ -207,6 +335,40 @@ impl Config {
             endpoints,
             out_of_sync_threshold: default_out_of_sync(),
             debug_fail: default_trace(),
+            wait_blocks: default_wait_blocks(),
+            dummy_account: default_false(),
+            result_file: None,
+            include_boc: default_false(),
+            check_int_range: default_false(),
+            fastest_endpoint: default_false(),
+            tree_depth: default_tree_depth(),
+            max_message_size: default_max_message_size(),
+            fee_config: None,
+            replay_protection: default_replay_protection(),
+            metrics_file: None,
+            relaxed_json: default_false(),
+            fees_on_abort: default_false(),
+            include_balance: default_false(),
+            poll_interval_ms: default_poll_interval_ms(),
+            decode_layout: default_false(),
+            config_address: None,
+            address_format: default_address_format(),
+            utc_time: default_false(),
+            check_storage_due: default_false(),
+            table_output: default_false(),
+            strict_output: default_false(),
+            structured_trace: default_false(),
+            check_frozen: default_false(),
+            emulate_src: None,
+            dump_encode_params: default_false(),
+            wait_level: default_wait_level(),
+            fees_percentage: default_false(),
+            debug_bc_config: None,
+            token_decimals: default_token_decimals(),
+            echo_params: default_false(),
+            unlimited_balance: default_false(),
+            canonical_json: default_false(),
+            include_timestamp: default_false(),
             project_id: None,
             access_key: None,
         }
@@ -272,6 +434,7 @@ impl FullConfig {
             config,
             endpoints_map: Self::default_map(),
             aliases: BTreeMap::new(),
+            networks: BTreeMap::new(),
             path,
         }
     }
@@ -340,6 +503,57 @@ impl FullConfig {
         self.to_file(&self.path)
     }
 
+    pub fn print_networks(&self) {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&self.networks).unwrap_or(
+                "Failed to print network profiles.".to_owned()
+            )
+        );
+    }
+
+    pub fn add_network(&mut self, name: &str, url: &str, endpoints: Option<&str>, config_address: Option<String>) -> Result<(), String> {
+        let endpoints = endpoints
+            .map(|endpoints| endpoints
+                .replace('[', "")
+                .replace(']', "")
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect())
+            .unwrap_or_default();
+        self.networks.insert(name.to_owned(), NetworkProfile { url: url.to_owned(), endpoints, config_address });
+        self.to_file(&self.path)
+    }
+
+    pub fn remove_network(&mut self, name: &str) -> Result<(), String> {
+        if self.networks.remove(name).is_none() {
+            return Err(format!("network profile \"{}\" is not defined", name));
+        }
+        self.to_file(&self.path)
+    }
+
+    /// Applies url, endpoints and config address of the named network profile to the config.
+    /// If the profile has no endpoints, they are taken from the endpoints map for its url.
+    pub fn select_network(&mut self, name: &str) -> Result<(), String> {
+        let profile = self.networks.get(name).cloned().ok_or_else(|| {
+            let names = self.networks.keys().map(|name| name.as_str()).collect::<Vec<_>>();
+            format!("network profile \"{}\" is not defined, known profiles: {}", name,
+                if names.is_empty() { "none".to_string() } else { names.join(", ") })
+        })?;
+        let url = resolve_net_name(&profile.url).unwrap_or(profile.url);
+        self.config.endpoints = if profile.endpoints.is_empty() {
+            self.endpoints_map.get(&url).cloned().unwrap_or_default()
+        } else {
+            profile.endpoints
+        };
+        self.config.url = url;
+        if profile.config_address.is_some() {
+            self.config.config_address = profile.config_address;
+        }
+        Ok(())
+    }
+
     pub fn add_endpoint(path: &str, url: &str, endpoints: &str) -> Result<(), String> {
         let mut fconf = FullConfig::from_file(path);
         let mut new_endpoints : Vec<String> = endpoints
@@ -441,6 +655,108 @@ pub fn clear_config(
     if matches.is_present("OUT_OF_SYNC") {
         config.out_of_sync_threshold = default_out_of_sync();
     }
+    if matches.is_present("WAIT_BLOCKS") {
+        config.wait_blocks = default_wait_blocks();
+    }
+    if matches.is_present("DUMMY_ACCOUNT") {
+        config.dummy_account = default_false();
+    }
+    if matches.is_present("RESULT_FILE") {
+        config.result_file = None;
+    }
+    if matches.is_present("INCLUDE_BOC") {
+        config.include_boc = default_false();
+    }
+    if matches.is_present("CHECK_INT_RANGE") {
+        config.check_int_range = default_false();
+    }
+    if matches.is_present("FASTEST_ENDPOINT") {
+        config.fastest_endpoint = default_false();
+    }
+    if matches.is_present("TREE_DEPTH") {
+        config.tree_depth = default_tree_depth();
+    }
+    if matches.is_present("MAX_MESSAGE_SIZE") {
+        config.max_message_size = default_max_message_size();
+    }
+    if matches.is_present("FEE_CONFIG") {
+        config.fee_config = None;
+    }
+    if matches.is_present("REPLAY_PROTECTION") {
+        config.replay_protection = default_replay_protection();
+    }
+    if matches.is_present("METRICS_FILE") {
+        config.metrics_file = None;
+    }
+    if matches.is_present("RELAXED_JSON") {
+        config.relaxed_json = default_false();
+    }
+    if matches.is_present("FEES_ON_ABORT") {
+        config.fees_on_abort = default_false();
+    }
+    if matches.is_present("INCLUDE_BALANCE") {
+        config.include_balance = default_false();
+    }
+    if matches.is_present("POLL_INTERVAL_MS") {
+        config.poll_interval_ms = default_poll_interval_ms();
+    }
+    if matches.is_present("DECODE_LAYOUT") {
+        config.decode_layout = default_false();
+    }
+    if matches.is_present("CONFIG_ADDRESS") {
+        config.config_address = None;
+    }
+    if matches.is_present("ADDRESS_FORMAT") {
+        config.address_format = default_address_format();
+    }
+    if matches.is_present("UTC_TIME") {
+        config.utc_time = default_false();
+    }
+    if matches.is_present("CHECK_STORAGE_DUE") {
+        config.check_storage_due = default_false();
+    }
+    if matches.is_present("TABLE_OUTPUT") {
+        config.table_output = default_false();
+    }
+    if matches.is_present("STRICT_OUTPUT") {
+        config.strict_output = default_false();
+    }
+    if matches.is_present("STRUCTURED_TRACE") {
+        config.structured_trace = default_false();
+    }
+    if matches.is_present("CHECK_FROZEN") {
+        config.check_frozen = default_false();
+    }
+    if matches.is_present("EMULATE_SRC") {
+        config.emulate_src = None;
+    }
+    if matches.is_present("DUMP_ENCODE_PARAMS") {
+        config.dump_encode_params = default_false();
+    }
+    if matches.is_present("WAIT_LEVEL") {
+        config.wait_level = default_wait_level();
+    }
+    if matches.is_present("FEES_PERCENTAGE") {
+        config.fees_percentage = default_false();
+    }
+    if matches.is_present("DEBUG_BC_CONFIG") {
+        config.debug_bc_config = None;
+    }
+    if matches.is_present("TOKEN_DECIMALS") {
+        config.token_decimals = default_token_decimals();
+    }
+    if matches.is_present("ECHO_PARAMS") {
+        config.echo_params = default_false();
+    }
+    if matches.is_present("UNLIMITED_BALANCE") {
+        config.unlimited_balance = default_false();
+    }
+    if matches.is_present("CANONICAL_JSON") {
+        config.canonical_json = default_false();
+    }
+    if matches.is_present("INCLUDE_TIMESTAMP") {
+        config.include_timestamp = default_false();
+    }
     if matches.is_present("IS_JSON") {
         config.is_json = default_false();
     }
@@ -564,6 +880,170 @@ pub fn set_config(
             return Err(r#"Wrong value for "debug_fail" config."#.to_string())
         };
     }
+    if let Some(wait_blocks) = matches.value_of("WAIT_BLOCKS") {
+        config.wait_blocks = u32::from_str_radix(wait_blocks, 10)
+            .map_err(|e| format!(r#"failed to parse "wait_blocks": {}"#, e))?;
+    }
+    if let Some(dummy_account) = matches.value_of("DUMMY_ACCOUNT") {
+        config.dummy_account = dummy_account.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "dummy_account": {}"#, e))?;
+    }
+    if let Some(result_file) = matches.value_of("RESULT_FILE") {
+        config.result_file = Some(result_file.to_string());
+    }
+    if let Some(include_boc) = matches.value_of("INCLUDE_BOC") {
+        config.include_boc = include_boc.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "include_boc": {}"#, e))?;
+    }
+    if let Some(check_int_range) = matches.value_of("CHECK_INT_RANGE") {
+        config.check_int_range = check_int_range.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "check_int_range": {}"#, e))?;
+    }
+    if let Some(fastest_endpoint) = matches.value_of("FASTEST_ENDPOINT") {
+        config.fastest_endpoint = fastest_endpoint.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "fastest_endpoint": {}"#, e))?;
+    }
+    if let Some(tree_depth) = matches.value_of("TREE_DEPTH") {
+        config.tree_depth = u32::from_str_radix(tree_depth, 10)
+            .map_err(|e| format!(r#"failed to parse "tree_depth": {}"#, e))?;
+    }
+    if let Some(max_message_size) = matches.value_of("MAX_MESSAGE_SIZE") {
+        config.max_message_size = u32::from_str_radix(max_message_size, 10)
+            .map_err(|e| format!(r#"failed to parse "max_message_size": {}"#, e))?;
+    }
+    if let Some(fee_config) = matches.value_of("FEE_CONFIG") {
+        synthetic_blockchain_config(fee_config)?;
+        config.fee_config = Some(fee_config.to_string());
+    }
+    if let Some(replay_protection) = matches.value_of("REPLAY_PROTECTION") {
+        let replay_protection = replay_protection.to_lowercase();
+        config.replay_protection = if replay_protection == "seqno" {
+            "Seqno".to_string()
+        } else if replay_protection == "none" {
+            "None".to_string()
+        } else {
+            return Err(r#"Wrong value for "replay_protection" config."#.to_string())
+        };
+    }
+    if let Some(metrics_file) = matches.value_of("METRICS_FILE") {
+        config.metrics_file = Some(metrics_file.to_string());
+    }
+    if let Some(relaxed_json) = matches.value_of("RELAXED_JSON") {
+        config.relaxed_json = relaxed_json.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "relaxed_json": {}"#, e))?;
+    }
+    if let Some(fees_on_abort) = matches.value_of("FEES_ON_ABORT") {
+        config.fees_on_abort = fees_on_abort.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "fees_on_abort": {}"#, e))?;
+    }
+    if let Some(include_balance) = matches.value_of("INCLUDE_BALANCE") {
+        config.include_balance = include_balance.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "include_balance": {}"#, e))?;
+    }
+    if let Some(poll_interval_ms) = matches.value_of("POLL_INTERVAL_MS") {
+        config.poll_interval_ms = u32::from_str_radix(poll_interval_ms, 10)
+            .map_err(|e| format!(r#"failed to parse "poll_interval_ms": {}"#, e))?;
+    }
+    if let Some(decode_layout) = matches.value_of("DECODE_LAYOUT") {
+        config.decode_layout = decode_layout.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "decode_layout": {}"#, e))?;
+    }
+    if let Some(config_address) = matches.value_of("CONFIG_ADDRESS") {
+        config.config_address = Some(config_address.to_string());
+    }
+    if let Some(address_format) = matches.value_of("ADDRESS_FORMAT") {
+        let address_format = address_format.to_lowercase();
+        config.address_format = if address_format == "raw" {
+            "Raw".to_string()
+        } else if address_format == "base64" {
+            "Base64".to_string()
+        } else if address_format == "none" {
+            "None".to_string()
+        } else {
+            return Err(r#"Wrong value for "address_format" config."#.to_string())
+        };
+    }
+    if let Some(utc_time) = matches.value_of("UTC_TIME") {
+        config.utc_time = utc_time.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "utc_time": {}"#, e))?;
+    }
+    if let Some(check_storage_due) = matches.value_of("CHECK_STORAGE_DUE") {
+        config.check_storage_due = check_storage_due.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "check_storage_due": {}"#, e))?;
+    }
+    if let Some(table_output) = matches.value_of("TABLE_OUTPUT") {
+        config.table_output = table_output.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "table_output": {}"#, e))?;
+    }
+    if let Some(strict_output) = matches.value_of("STRICT_OUTPUT") {
+        config.strict_output = strict_output.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "strict_output": {}"#, e))?;
+    }
+    if let Some(structured_trace) = matches.value_of("STRUCTURED_TRACE") {
+        config.structured_trace = structured_trace.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "structured_trace": {}"#, e))?;
+    }
+    if let Some(check_frozen) = matches.value_of("CHECK_FROZEN") {
+        config.check_frozen = check_frozen.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "check_frozen": {}"#, e))?;
+    }
+    if let Some(emulate_src) = matches.value_of("EMULATE_SRC") {
+        config.emulate_src = if emulate_src.is_empty() {
+            None
+        } else {
+            parse_ext_address(emulate_src)?;
+            Some(emulate_src.to_string())
+        };
+    }
+    if let Some(dump_encode_params) = matches.value_of("DUMP_ENCODE_PARAMS") {
+        config.dump_encode_params = dump_encode_params.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "dump_encode_params": {}"#, e))?;
+    }
+    if let Some(wait_level) = matches.value_of("WAIT_LEVEL") {
+        let wait_level = wait_level.to_lowercase();
+        config.wait_level = if wait_level == "accepted" {
+            "Accepted".to_string()
+        } else if wait_level == "processed" {
+            "Processed".to_string()
+        } else {
+            return Err(r#"Wrong value for "wait_level" config."#.to_string())
+        };
+    }
+    if let Some(fees_percentage) = matches.value_of("FEES_PERCENTAGE") {
+        config.fees_percentage = fees_percentage.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "fees_percentage": {}"#, e))?;
+    }
+    if let Some(debug_bc_config) = matches.value_of("DEBUG_BC_CONFIG") {
+        config.debug_bc_config = if debug_bc_config.is_empty() {
+            None
+        } else {
+            Some(debug_bc_config.to_string())
+        };
+    }
+    if let Some(token_decimals) = matches.value_of("TOKEN_DECIMALS") {
+        let token_decimals = u32::from_str_radix(token_decimals, 10)
+            .map_err(|e| format!(r#"failed to parse "token_decimals": {}"#, e))?;
+        if token_decimals > 38 {
+            return Err(r#""token_decimals" must not be greater than 38"#.to_string());
+        }
+        config.token_decimals = token_decimals;
+    }
+    if let Some(echo_params) = matches.value_of("ECHO_PARAMS") {
+        config.echo_params = echo_params.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "echo_params": {}"#, e))?;
+    }
+    if let Some(unlimited_balance) = matches.value_of("UNLIMITED_BALANCE") {
+        config.unlimited_balance = unlimited_balance.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "unlimited_balance": {}"#, e))?;
+    }
+    if let Some(canonical_json) = matches.value_of("CANONICAL_JSON") {
+        config.canonical_json = canonical_json.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "canonical_json": {}"#, e))?;
+    }
+    if let Some(include_timestamp) = matches.value_of("INCLUDE_TIMESTAMP") {
+        config.include_timestamp = include_timestamp.parse::<bool>()
+            .map_err(|e| format!(r#"failed to parse "include_timestamp": {}"#, e))?;
+    }
     if let Some(is_json) = matches.value_of("IS_JSON") {
         config.is_json = is_json.parse::<bool>()
             .map_err(|e| format!(r#"failed to parse "is_json": {}"#, e))?;
@@ -32,7 +32,7 @@ pub fn convert_amount(amount: &str, decimals: usize) -> Result<String, String> {
             result += &"0".repeat(decimals);
         }
         let result = result.trim_start_matches('0').to_string();
-        u64::from_str_radix(&result, 10)
+        u128::from_str_radix(&result, 10)
             .map_err(|e| format!("failed to parse amount: {}", e))?;
 
         return Ok(result);
@@ -41,9 +41,16 @@ pub fn convert_amount(amount: &str, decimals: usize) -> Result<String, String> {
 }
 
 pub fn convert_u64_to_tokens(value: u64) -> String {
-    let integer = value / 1_000_000_000;
-    let float = value - integer * 1_000_000_000;
-    format!("{}.{:>09}", integer, float)
+    convert_u128_to_amount(value as u128, 9)
+}
+
+pub fn convert_u128_to_amount(value: u128, decimals: usize) -> String {
+    if decimals == 0 {
+        return value.to_string();
+    }
+    let value = format!("{:0>width$}", value, width = decimals + 1);
+    let (integer, fraction) = value.split_at(value.len() - decimals);
+    format!("{}.{}", integer, fraction)
 }
 
 pub fn nodeid_from_pubkey(key: &[u8]) -> Result<String, String> {
@@ -22,12 +22,33 @@ use ton_client::crypto::{
     ParamsOfHDKeyDeriveFromXPrvPath,
     ParamsOfHDKeyXPrvFromMnemonic,
     ParamsOfNaclSignKeyPairFromSecret,
-    ParamsOfMnemonicFromRandom
+    ParamsOfMnemonicFromRandom,
+    generate_random_bytes,
+    nacl_secret_box,
+    nacl_secret_box_open,
+    scrypt,
+    ParamsOfGenerateRandomBytes,
+    ParamsOfNaclSecretBox,
+    ParamsOfNaclSecretBoxOpen,
+    ParamsOfScrypt,
 };
 use crate::Config;
+use serde_json::{json, Value};
+use std::io::Write;
+
+// Environment variable with passphrase of the encrypted keypair file.
+// If it is not set, passphrase is requested from the terminal.
+const KEYS_PASSPHRASE_ENV: &str = "TONOSCLI_KEYS_PASSPHRASE";
+const SCRYPT_LOG_N: u8 = 14;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
 
 pub fn load_keypair(keys: &str) -> Result<KeyPair, String> {
     if keys.find(' ').is_none() {
+        if let Some(encrypted) = read_encrypted_keys(keys) {
+            let passphrase = read_passphrase(keys)?;
+            return decrypt_keypair(&encrypted, &passphrase);
+        }
         let keys = read_keys(keys)?;
         Ok(keys)
     } else {
@@ -35,6 +56,189 @@ pub fn load_keypair(keys: &str) -> Result<KeyPair, String> {
     }
 }
 
+// Key file name without `.keys.json` or `.json` extension.
+fn keyfile_label(file_name: &str) -> &str {
+    file_name.strip_suffix(".keys.json")
+        .or_else(|| file_name.strip_suffix(".json"))
+        .unwrap_or(file_name)
+}
+
+/// If `keys` is a directory, selects the key file from it by the label (if specified) or by the address.
+/// Key file matches if its name without extension equals the label, the address or the account id part
+/// of the address. Otherwise `keys` is returned as is.
+pub fn select_keyfile(keys: Option<String>, label: Option<&str>, address: &str) -> Result<Option<String>, String> {
+    let dir = match keys {
+        Some(dir) if std::path::Path::new(&dir).is_dir() => dir,
+        keys => return Ok(keys),
+    };
+    let candidates = match label {
+        Some(label) => vec![label.to_owned()],
+        None => vec![
+            address.to_owned(),
+            address.split_once(':').map(|(_, id)| id.to_owned()).unwrap_or_default(),
+        ],
+    };
+    let mut files = std::fs::read_dir(&dir)
+        .map_err(|e| format!("failed to read keys directory {}: {}", dir, e))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    files.sort();
+    match files.iter().find(|name| candidates.iter().any(|c| !c.is_empty() && c == keyfile_label(name))) {
+        Some(name) => Ok(Some(std::path::Path::new(&dir).join(name).to_string_lossy().to_string())),
+        None => Err(format!("no key file in directory {} matches {}, available keys: {}",
+            dir,
+            label.unwrap_or(address),
+            files.iter().map(|name| keyfile_label(name)).collect::<Vec<_>>().join(", "))),
+    }
+}
+
+/// Loads the keypair and checks that its public key is derived from the secret key.
+/// Returns the public key in hex.
+pub fn validate_keys(path: &str) -> Result<String, String> {
+    let keys = load_keypair(path)?;
+    let decode_key = |key: &str, name: &str| -> Result<Vec<u8>, String> {
+        let bytes = hex::decode(key)
+            .map_err(|e| format!("failed to decode {} key: {}", name, e))?;
+        if bytes.len() != ed25519_dalek::SECRET_KEY_LENGTH {
+            return Err(format!("{} key must be {} bytes long, got {} bytes",
+                name, ed25519_dalek::SECRET_KEY_LENGTH, bytes.len()));
+        }
+        Ok(bytes)
+    };
+    let secret = decode_key(&keys.secret, "secret")?;
+    let public = decode_key(&keys.public, "public")?;
+    let secret = ed25519_dalek::SecretKey::from_bytes(&secret)
+        .map_err(|e| format!("invalid secret key: {}", e))?;
+    let derived = ed25519_dalek::PublicKey::from(&secret);
+    if derived.as_bytes()[..] != public[..] {
+        return Err(format!("public key doesn't match the secret key, public key derived from the secret is {}",
+            hex::encode(derived.as_bytes())));
+    }
+    Ok(keys.public)
+}
+
+pub fn check_keypair(path: &str, is_json: bool) -> Result<(), String> {
+    let public = validate_keys(path)?;
+    if !is_json {
+        println!("Keypair is valid.");
+        println!("Public key: {}", public);
+    } else {
+        println!("{{");
+        println!("  \"Public key\": \"{}\"", public);
+        println!("}}");
+    }
+    Ok(())
+}
+
+// returns content of the keypair file if it is an encrypted one
+fn read_encrypted_keys(path: &str) -> Option<Value> {
+    let data = std::fs::read_to_string(path).ok()?;
+    let data: Value = serde_json::from_str(&data).ok()?;
+    if data.get("ciphertext").is_some() { Some(data) } else { None }
+}
+
+fn read_passphrase(path: &str) -> Result<String, String> {
+    if let Ok(passphrase) = std::env::var(KEYS_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    eprint!("Enter passphrase for the keypair file {}: ", path);
+    std::io::stderr().flush()
+        .map_err(|e| format!("failed to request passphrase: {}", e))?;
+    rpassword::read_password()
+        .map_err(|e| format!("failed to read passphrase: {}", e))
+}
+
+fn derive_key(passphrase: &str, salt: &str, log_n: u8, r: u32, p: u32) -> Result<String, String> {
+    let client = create_client_local()?;
+    scrypt(
+        client,
+        ParamsOfScrypt {
+            password: base64::encode(passphrase.as_bytes()),
+            salt: salt.to_owned(),
+            log_n,
+            r,
+            p,
+            dk_len: 32,
+        },
+    )
+    .map_err(|e| format!("failed to derive key from passphrase: {}", e))
+    .map(|r| r.key)
+}
+
+/// Encrypts keypair with the key derived from passphrase with scrypt.
+/// Returns json with kdf parameters, salt, nonce and encrypted keypair.
+pub fn encrypt_keypair(keys: &KeyPair, passphrase: &str) -> Result<Value, String> {
+    let client = create_client_local()?;
+    let random_bytes = |length| generate_random_bytes(
+        client.clone(),
+        ParamsOfGenerateRandomBytes { length },
+    )
+    .map_err(|e| format!("failed to generate random bytes: {}", e))
+    .map(|r| r.bytes);
+    let salt = random_bytes(32)?;
+    let nonce = base64::decode(random_bytes(24)?)
+        .map_err(|e| format!("failed to decode random bytes: {}", e))?;
+    let nonce = hex::encode(nonce);
+
+    let key = derive_key(passphrase, &salt, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)?;
+    let keys_json = serde_json::to_string(keys)
+        .map_err(|e| format!("failed to serialize the keypair: {}", e))?;
+    let encrypted = nacl_secret_box(
+        client,
+        ParamsOfNaclSecretBox {
+            decrypted: base64::encode(keys_json.as_bytes()),
+            nonce: nonce.clone(),
+            key,
+        },
+    )
+    .map_err(|e| format!("failed to encrypt the keypair: {}", e))?
+    .encrypted;
+
+    Ok(json!({
+        "kdf": "scrypt",
+        "log_n": SCRYPT_LOG_N,
+        "r": SCRYPT_R,
+        "p": SCRYPT_P,
+        "salt": salt,
+        "nonce": nonce,
+        "ciphertext": encrypted,
+    }))
+}
+
+pub fn decrypt_keypair(encrypted: &Value, passphrase: &str) -> Result<KeyPair, String> {
+    let field = |name: &str| encrypted[name].as_str()
+        .ok_or(format!(r#"encrypted keypair file is corrupted: "{}" field not found"#, name));
+    let number = |name: &str| encrypted[name].as_u64()
+        .ok_or(format!(r#"encrypted keypair file is corrupted: "{}" field not found"#, name));
+    if field("kdf")? != "scrypt" {
+        return Err(format!("encrypted keypair file uses unsupported kdf: {}", field("kdf")?));
+    }
+    let key = derive_key(
+        passphrase,
+        field("salt")?,
+        number("log_n")? as u8,
+        number("r")? as u32,
+        number("p")? as u32,
+    )?;
+    let client = create_client_local()?;
+    let decrypted = nacl_secret_box_open(
+        client,
+        ParamsOfNaclSecretBoxOpen {
+            encrypted: field("ciphertext")?.to_owned(),
+            nonce: field("nonce")?.to_owned(),
+            key,
+        },
+    )
+    .map_err(|_| "failed to decrypt keypair file: wrong passphrase or the file is corrupted".to_string())?
+    .decrypted;
+    let decrypted = base64::decode(decrypted)
+        .map_err(|e| format!("failed to decode decrypted keypair: {}", e))?;
+    serde_json::from_slice::<KeyPair>(&decrypted)
+        .map_err(|e| format!("failed to load decrypted keypair: {}", e))
+}
+
 pub fn gen_seed_phrase() -> Result<String, String> {
     let client = create_client_local()?;
     mnemonic_from_random(
@@ -124,7 +328,7 @@ pub fn generate_mnemonic(keypath: Option<&str>, config: &Config) -> Result<(), S
         println!("}}");
     }
     if let Some(path) = keypath {
-        generate_keypair(Some(path), Some(&mnemonic), config)?;
+        generate_keypair(Some(path), Some(&mnemonic), false, config)?;
         if !config.is_json {
             println!("Keypair saved to {}", path);
         }
@@ -149,7 +353,7 @@ pub fn extract_pubkey(mnemonic: &str, is_json: bool) -> Result<(), String> {
     Ok(())
 }
 
-pub fn generate_keypair(keys_path: Option<&str>, mnemonic: Option<&str>, config: &Config) -> Result<(), String> {
+pub fn generate_keypair(keys_path: Option<&str>, mnemonic: Option<&str>, encrypt: bool, config: &Config) -> Result<(), String> {
     let mnemonic = match mnemonic {
         Some(mnemonic) => mnemonic.to_owned(),
         None => {
@@ -169,8 +373,15 @@ pub fn generate_keypair(keys_path: Option<&str>, mnemonic: Option<&str>, config:
     } else {
         generate_keypair_from_secret(mnemonic)?
     };
-    let keys_json = serde_json::to_string_pretty(&keys)
-        .map_err(|e| format!("failed to serialize the keypair: {}", e))?;
+    if encrypt && keys_path.is_none() {
+        return Err("keypair can be encrypted only when it is saved to the file".to_owned());
+    }
+    let keys_json = if let (true, Some(keys_path)) = (encrypt, keys_path) {
+        let passphrase = read_passphrase(keys_path)?;
+        serde_json::to_string_pretty(&encrypt_keypair(&keys, &passphrase)?)
+    } else {
+        serde_json::to_string_pretty(&keys)
+    }.map_err(|e| format!("failed to serialize the keypair: {}", e))?;
     if let Some(keys_path) = keys_path {
         let folder_path = keys_path
             .trim_end_matches(|c| c != '/')
@@ -210,6 +421,63 @@ mod tests {
         assert_eq!(&keypair.secret, "f63d3d11e0dc91f730f22d5397f269e01f1a5f984879c8581ac87f099bfd3b3a");
     }
 
+    #[test]
+    fn test_select_keyfile() {
+        let dir = std::env::temp_dir().join("tonos_cli_test_select_keyfile");
+        std::fs::create_dir_all(&dir).unwrap();
+        let account_id = "841288ed3b55d9cdafa806807f02a0ae0c169aa5edfe88a789a6482429756a94";
+        std::fs::write(dir.join(format!("{}.keys.json", account_id)), "{}").unwrap();
+        std::fs::write(dir.join("owner.json"), "{}").unwrap();
+        let dir_path = dir.to_string_lossy().to_string();
+
+        let address = format!("0:{}", account_id);
+        let selected = select_keyfile(Some(dir_path.clone()), None, &address).unwrap().unwrap();
+        assert!(selected.ends_with(&format!("{}.keys.json", account_id)));
+        let selected = select_keyfile(Some(dir_path.clone()), Some("owner"), &address).unwrap().unwrap();
+        assert!(selected.ends_with("owner.json"));
+        assert!(select_keyfile(Some(dir_path.clone()), Some("other"), &address).is_err());
+        assert!(select_keyfile(Some(dir_path), None, "0:1111").is_err());
+        assert_eq!(select_keyfile(Some("key.json".to_owned()), None, &address).unwrap(), Some("key.json".to_owned()));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_validate_keys() {
+        let mnemonic = "multiply extra monitor fog rocket defy attack right night jaguar hollow enlist";
+        assert_eq!(validate_keys(mnemonic).unwrap(), "757221fe3d4992e44632e75e700aaf205d799cb7373ee929273daf26adf29e56");
+
+        let path = std::env::temp_dir().join("tonos_cli_test_validate_keys.json");
+        let path_str = path.to_str().unwrap();
+        std::fs::write(&path, r#"{
+            "public": "8cf557aab2666867a1174e3147d89ddf28c2041a7322522276cd1cf1df47ae73",
+            "secret": "30e3bc5e67af2b0a72971bcc11256e83d052c6cb861a69a19a8af88922fadf3a"
+        }"#).unwrap();
+        assert!(validate_keys(path_str).unwrap_err().contains("757221fe3d4992e44632e75e700aaf205d799cb7373ee929273daf26adf29e56"));
+
+        std::fs::write(&path, r#"{
+            "public": "757221fe3d4992e44632e75e700aaf205d799cb7373ee929273daf26adf29e56",
+            "secret": "30e3bc5e67af2b0a72971bcc11256e83d052c6cb861a69a19a8af88922fadf"
+        }"#).unwrap();
+        assert!(validate_keys(path_str).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_keypair() {
+        let mnemonic = "multiply extra monitor fog rocket defy attack right night jaguar hollow enlist";
+        let keypair = generate_keypair_from_mnemonic(mnemonic).unwrap();
+        let encrypted = encrypt_keypair(&keypair, "passphrase").unwrap();
+        assert!(!encrypted.to_string().contains(&keypair.secret));
+
+        let decrypted = decrypt_keypair(&encrypted, "passphrase").unwrap();
+        assert_eq!(decrypted.public, keypair.public);
+        assert_eq!(decrypted.secret, keypair.secret);
+
+        let err = decrypt_keypair(&encrypted, "wrong").unwrap_err();
+        assert!(!err.contains(&keypair.secret));
+    }
+
     #[test]
     fn test_invalid_mnemonic() {
         let invalid_phrases = vec![
@@ -30,7 +30,7 @@ use ton_client::abi::{CallSet, Signer, FunctionHeader, encode_message, ParamsOfE
 use ton_executor::{
     BlockchainConfig, ExecuteParams, OrdinaryTransactionExecutor, TransactionExecutor
 };
-use std::sync::{Arc, atomic::AtomicU64};
+use std::sync::{Arc, Mutex, atomic::AtomicU64};
 use ton_client::net::{OrderBy, ParamsOfQueryCollection, query_collection, SortDirection};
 use crate::crypto::load_keypair;
 use std::fmt;
@@ -1054,6 +1054,61 @@ pub async fn execute_debug(
     last_tr_lt: u64,
     is_getter: bool,
     tonos_config: &Config,
+) -> Result<Transaction, String> {
+    let trace_callback = generate_callback(None, tonos_config);
+    execute_debug_with_callback(bc_config, account, message, block_unixtime, block_lt, last_tr_lt, is_getter, trace_callback)
+}
+
+/// The same as `execute_debug`, but the trace is also collected in memory and returned along with the
+/// transaction. Each step is an object with `step`, `cmd`, `gas_used`, `gas_cmd` and `stack` fields.
+/// Trace of a long execution may take a lot of memory.
+pub async fn execute_debug_with_trace(
+    bc_config: BlockchainConfig,
+    account: &mut Cell,
+    message: Option<&Message>,
+    block_unixtime: u32,
+    block_lt: u64,
+    last_tr_lt: u64,
+    is_getter: bool,
+    tonos_config: &Config,
+) -> (Result<Transaction, String>, Vec<Value>) {
+    let steps = Arc::new(Mutex::new(vec![]));
+    let trace = steps.clone();
+    let log_callback = generate_callback(None, tonos_config);
+    let trace_callback: Arc<dyn Fn(&Engine, &EngineTraceInfo) + Send + Sync> = Arc::new(move |engine, info| {
+        if let Some(log_callback) = &log_callback {
+            log_callback(engine, info);
+        }
+        trace.lock().unwrap().push(trace_step(info));
+    });
+    let result = execute_debug_with_callback(bc_config, account, message, block_unixtime, block_lt, last_tr_lt,
+        is_getter, Some(trace_callback));
+    let steps = steps.lock().unwrap().drain(..).collect();
+    (result, steps)
+}
+
+fn trace_step(info: &EngineTraceInfo) -> Value {
+    if info.info_type == EngineTraceInfoType::Dump {
+        return json!({ "dump": info.cmd_str });
+    }
+    json!({
+        "step": info.step,
+        "cmd": info.cmd_str,
+        "gas_used": info.gas_used,
+        "gas_cmd": info.gas_cmd,
+        "stack": info.stack.iter().map(|item| item.to_string()).collect::<Vec<_>>(),
+    })
+}
+
+fn execute_debug_with_callback(
+    bc_config: BlockchainConfig,
+    account: &mut Cell,
+    message: Option<&Message>,
+    block_unixtime: u32,
+    block_lt: u64,
+    last_tr_lt: u64,
+    is_getter: bool,
+    trace_callback: Option<Arc<dyn Fn(&Engine, &EngineTraceInfo) + Send + Sync>>,
 ) -> Result<Transaction, String> {
     let bc_config = if is_getter {
         let mut config = bc_config.raw_config().to_owned();
@@ -1090,7 +1145,7 @@ pub async fn execute_debug(
         last_tr_lt: Arc::new(AtomicU64::new(last_tr_lt)),
         seed_block: UInt256::default(),
         debug: true,
-        trace_callback: generate_callback(None, tonos_config),
+        trace_callback,
         ..ExecuteParams::default()
     };
 
@@ -11,6 +11,7 @@
  * limitations under the License.
  */
 use crate::{load_abi, print_args};
+use crate::call::{decode_call_message, list_functions};
 use crate::config::Config;
 use crate::decode::msg_printer::tree_of_cells_into_base64;
 use crate::helpers::{decode_msg_body, print_account, create_client_local, create_client_verbose, query_account_field, abi_from_matches_or_config, load_ton_address, load_ton_abi, create_client, query_message};
@@ -63,7 +64,22 @@ pub fn create_decode_command<'a, 'b>() -> App<'a, 'b> {
             .arg(Arg::with_name("BASE64")
                 .long("--base64")
                 .help("Flag that changes behavior of the command to work with data in base64 (FLAG IS DEPRECATED).")))
+        .subcommand(SubCommand::with_name("call")
+            .about("Decodes function call from the message without sending it.")
+            .arg(Arg::with_name("MESSAGE")
+                .required(true)
+                .help("Message generated by the message command or message boc in base64."))
+            .arg(Arg::with_name("ABI")
+                .long("--abi")
+                .takes_value(true)
+                .help("Path or link to the contract ABI file or pure json ABI data. Can be specified in the config file.")))
         .subcommand(tvc_cmd)
+        .subcommand(SubCommand::with_name("abi")
+            .about("Prints functions of the contract ABI with their ids.")
+            .arg(Arg::with_name("ABI")
+                .long("--abi")
+                .takes_value(true)
+                .help("Path or link to the contract ABI file or pure json ABI data. Can be specified in the config file.")))
         .subcommand(SubCommand::with_name("account")
             .about("Top level command of account decode commands.")
             .subcommand(SubCommand::with_name("data")
@@ -104,9 +120,16 @@ pub async fn decode_command(m: &ArgMatches<'_>, config: &Config) -> Result<(), S
     if let Some(m) = m.subcommand_matches("msg") {
         return decode_message_command(m, config).await;
     }
+    if let Some(m) = m.subcommand_matches("call") {
+        let abi = abi_from_matches_or_config(m, config)?;
+        return decode_call_message(config, m.value_of("MESSAGE").unwrap(), &abi).await;
+    }
     if let Some(m) = m.subcommand_matches("stateinit") {
         return decode_tvc_command(m, config).await;
     }
+    if let Some(m) = m.subcommand_matches("abi") {
+        return decode_abi_command(m, config).await;
+    }
     if let Some(m) = m.subcommand_matches("account") {
         if let Some(m) = m.subcommand_matches("boc") {
             return decode_account_from_boc(m, config).await;
@@ -128,6 +151,18 @@ async fn decode_data_command(m: &ArgMatches<'_>, config: &Config) -> Result<(),
     Err("unknown command".to_owned())
 }
 
+async fn decode_abi_command(m: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
+    let abi = Some(abi_from_matches_or_config(m, &config)?);
+    if !config.is_json {
+        print_args!(abi);
+    }
+    let contract = load_ton_abi(&abi.unwrap(), config).await?;
+    let functions = list_functions(&contract);
+    println!("{}", serde_json::to_string_pretty(&functions)
+        .map_err(|e| format!("failed to serialize the result: {}", e))?);
+    Ok(())
+}
+
 async fn decode_body_command(m: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
     let body = m.value_of("BODY");
     let abi = Some(abi_from_matches_or_config(m, &config)?);
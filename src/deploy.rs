@@ -10,18 +10,24 @@
  * See the License for the specific TON DEV software governing permissions and
  * limitations under the License.
  */
-use crate::helpers::{create_client_verbose, create_client_local, load_abi, calc_acc_address};
+use crate::helpers::{TonClient, create_client_verbose, create_client_local, load_abi, calc_acc_address,
+                     fee_blockchain_config};
 use crate::config::{FullConfig};
 use crate::crypto::load_keypair;
 use crate::call::{
+    dummy_account_boc,
     emulate_locally,
+    EmulateOptions,
+    print_fees,
     process_message,
     send_message_and_wait,
+    wait_for_masterchain_blocks,
 };
 use ton_client::abi::{
     encode_message, Signer, CallSet, DeploySet, ParamsOfEncodeMessage, Abi,
 };
 use ton_client::crypto::KeyPair;
+use ton_client::tvm::{run_executor, AccountForExecutor, ExecutionOptions, ParamsOfRunExecutor, TransactionFees};
 use crate::Config;
 use crate::message::{display_generated_message, EncodedMessage};
 
@@ -42,16 +48,31 @@ pub async fn deploy_contract(
         println!("Deploying...");
     }
 
+    if is_fee {
+        let abi = load_abi(abi, config).await?;
+        let keys = keys_file.map(|k| load_keypair(&k)).transpose()?;
+        let tvc_bytes = std::fs::read(tvc)
+            .map_err(|e| format!("failed to read smart contract file: {}", e))?;
+        let bc_config = fee_blockchain_config(config)?;
+        let fees = estimate_deploy_fee(ton, &tvc_bytes, abi, params, keys, wc, bc_config).await?;
+        print_fees(&fees);
+        return Ok(());
+    }
+
     let (msg, addr) = prepare_deploy_message(tvc, abi, params, keys_file.clone(), wc, &full_config.config).await?;
 
     let enc_msg = encode_message(ton.clone(), msg.clone()).await
         .map_err(|e| format!("failed to create inbound message: {}", e))?;
 
-    if config.local_run || is_fee {
-        emulate_locally(ton.clone(), addr.as_str(), enc_msg.message.clone(), is_fee).await?;
-        if is_fee {
-            return Ok(());
-        }
+    if config.local_run {
+        let bc_config = fee_blockchain_config(config)?;
+        emulate_locally(ton.clone(), addr.as_str(), enc_msg.message.clone(), EmulateOptions {
+            allow_dummy: config.dummy_account,
+            bc_config,
+            src: config.emulate_src.as_deref(),
+            unlimited_balance: config.unlimited_balance,
+            ..Default::default()
+        }).await?;
     }
 
     if config.async_call {
@@ -63,6 +84,7 @@ pub async fn deploy_contract(
     } else {
         process_message(ton.clone(), msg, config).await
             .map_err(|e| format!("{:#}", e))?;
+        wait_for_masterchain_blocks(ton, config).await?;
     }
 
     if !config.is_json {
@@ -79,6 +101,40 @@ pub async fn deploy_contract(
     Ok(())
 }
 
+/// Estimates fees of the contract deploy by running the deploy message on a synthesized
+/// uninitialized account with unlimited balance.
+pub async fn estimate_deploy_fee(
+    ton: TonClient,
+    tvc_bytes: &[u8],
+    abi: Abi,
+    params: &str,
+    keys: Option<KeyPair>,
+    wc: i32,
+    bc_config: Option<String>,
+) -> Result<TransactionFees, String> {
+    let (msg, addr) = prepare_deploy_message_params(tvc_bytes, abi, params, keys, wc).await?;
+    let msg = encode_message(ton.clone(), msg).await
+        .map_err(|e| format!("failed to create inbound message: {}", e))?;
+
+    let result = run_executor(
+        ton,
+        ParamsOfRunExecutor {
+            message: msg.message,
+            account: AccountForExecutor::Account {
+                boc: dummy_account_boc(&addr)?,
+                unlimited_balance: Some(true),
+            },
+            execution_options: Some(ExecutionOptions {
+                blockchain_config: bc_config,
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+    ).await
+        .map_err(|e| format!("{:#}", e))?;
+    Ok(result.fees)
+}
+
 pub async fn generate_deploy_message(
     tvc: &str,
     abi: &str,
@@ -102,7 +158,7 @@ pub async fn generate_deploy_message(
         expire: None,
         address: addr.to_owned(),
     };
-    display_generated_message(&msg, "constructor", is_raw, output, config.is_json)?;
+    display_generated_message(&msg, "constructor", is_raw, output, config)?;
     if !config.is_json {
         println!("Contract's address: {}", addr);
         println!("Succeeded.");
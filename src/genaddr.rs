@@ -11,12 +11,12 @@
  * limitations under the License.
  */
 use crate::config::Config;
-use crate::helpers::{create_client_local, read_keys, load_abi, calc_acc_address, load_abi_str};
+use crate::helpers::{create_client_local, load_abi, calc_acc_address, load_abi_str};
 use ed25519_dalek::PublicKey;
 use serde_json::json;
 use std::fs::OpenOptions;
 
-use crate::crypto::{gen_seed_phrase, generate_keypair_from_mnemonic};
+use crate::crypto::{gen_seed_phrase, generate_keypair_from_mnemonic, load_keypair};
 use ton_client::utils::{convert_address, ParamsOfConvertAddress, AddressStringFormat};
 
 pub async fn generate_address(
@@ -46,7 +46,7 @@ pub async fn generate_address(
     let keys = if !phrase.is_empty() {
         Some(generate_keypair_from_mnemonic(&phrase)?)
     } else if keys_file.is_some() {
-        Some(read_keys(keys_file.unwrap())?)
+        Some(load_keypair(keys_file.unwrap())?)
     } else {
         None
     };
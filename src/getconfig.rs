@@ -11,17 +11,19 @@
  * limitations under the License.
  */
 
-use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signature, Signer, Verifier};
 use num_bigint::BigUint;
 use std::time::{SystemTime, UNIX_EPOCH};
+use crate::call::execute_locally;
 use crate::config::Config;
-use crate::helpers::{create_client_verbose, query_with_limit};
+use crate::helpers::{create_client_verbose, fee_blockchain_config, query_account_field, query_with_limit};
 use serde_json::json;
 use ton_abi::{Contract, Token, TokenValue, Uint};
-use ton_block::{ExternalInboundMessageHeader, Grams, Message, MsgAddressInt, Serializable};
+use ton_block::{ConfigParamEnum, ConfigParams, Deserializable, ExternalInboundMessageHeader, Grams, Message, MsgAddressInt, Serializable};
 use ton_block::MsgAddressExt::AddrNone;
 use ton_client::net::{OrderBy, SortDirection};
 use ton_client::boc::{get_blockchain_config, ParamsOfGetBlockchainConfig};
+use ton_client::tvm::TransactionFees;
 use ton_types::{BuilderData, Cell, IBitstring, SliceData};
 
 const PREFIX_UPDATE_CONFIG_MESSAGE_DATA: &str = "43665021";
@@ -325,39 +327,332 @@ pub async fn gen_update_config_message(
     seqno: Option<&str>,
     config_master_file: &str,
     new_param_file: &str,
+    param_index: Option<u32>,
+    preview: bool,
     is_json: bool
 ) -> Result<(), String> {
-    let config_master_address = std::fs::read(&*(config_master_file.to_string() + ".addr"))
-        .map_err(|e| format!(r#"failed to read "config_master": {}"#, e))?;
-    let config_account = ton_types::AccountId::from_raw(config_master_address, 32*8);
-
-    let private_key_of_config_account = std::fs::read(&*(config_master_file.to_string() + ".pk"))
-        .map_err(|e| format!(r#"failed to read "config_master": {}"#, e))?;
-
-    let config_str = std::fs::read_to_string(new_param_file)
-        .map_err(|e| format!(r#"failed to read "new_param_file": {}"#, e))?;
-
-    let (config_cell, key_number) = serialize_config_param(config_str)?;
-    let message = if let Some(abi) = abi {
-        prepare_message_new_config_param_solidity(abi, config_cell, key_number, config_account, &private_key_of_config_account)?
+    let (config_master_address, private_key_of_config_account) = load_config_master(config_master_file)?;
+    let seqno = if abi.is_none() { Some(parse_seqno(seqno)?) } else { None };
+    let (config_cell, key_number) = load_new_config_param(new_param_file, param_index)?;
+    let param = if preview {
+        Some(config_param_preview(&config_cell, key_number)?)
     } else {
-        let seqno = seqno.unwrap().parse().map_err(|e| format!(r#"failed to parse "seqno": {}"#, e))?;
-        prepare_message_new_config_param(config_cell, seqno, key_number, config_account, &private_key_of_config_account)?
+        None
     };
+    let message = build_update_config_message(
+        abi,
+        seqno,
+        config_master_address,
+        &private_key_of_config_account,
+        config_cell,
+        key_number,
+    )?;
 
     let msg_bytes = message.write_to_bytes()
         .map_err(|e| format!(r#"failed to serialize message": {}"#, e))?;
     let msg_hex = hex::encode(&msg_bytes);
 
     if is_json {
-        println!("{{\"Message\": \"{}\"}}", msg_hex);
+        match param {
+            Some(param) => println!("{:#}", json!({ "Param": param, "Message": msg_hex })),
+            None => println!("{{\"Message\": \"{}\"}}", msg_hex),
+        }
     } else {
+        if let Some(param) = param {
+            println!("Param: {:#}", param);
+        }
         println!("Message: {}", msg_hex);
     }
 
     Ok(())
 }
 
+// reads address of the config account and its private key from `<file>.addr` and `<file>.pk`
+fn load_config_master(config_master_file: &str) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let config_master_address = std::fs::read(&*(config_master_file.to_string() + ".addr"))
+        .map_err(|e| format!(r#"failed to read "config_master": {}"#, e))?;
+    let private_key_of_config_account = std::fs::read(&*(config_master_file.to_string() + ".pk"))
+        .map_err(|e| format!(r#"failed to read "config_master": {}"#, e))?;
+    Ok((config_master_address, private_key_of_config_account))
+}
+
+fn parse_seqno(seqno: Option<&str>) -> Result<u32, String> {
+    seqno.ok_or(r#""seqno" is not specified"#.to_string())?
+        .parse().map_err(|e| format!(r#"failed to parse "seqno": {}"#, e))
+}
+
+// loads config param cell from the file with json of the param or with boc of the param cell
+// if its index is specified
+fn load_new_config_param(new_param_file: &str, param_index: Option<u32>) -> Result<(Cell, u32), String> {
+    let config_str = std::fs::read_to_string(new_param_file)
+        .map_err(|e| format!(r#"failed to read "new_param_file": {}"#, e))?;
+    match param_index {
+        Some(key_number) => Ok((config_param_from_boc(&config_str, key_number)?, key_number)),
+        None => serialize_config_param(config_str),
+    }
+}
+
+/// Deserializes the config param cell back and converts it to json in the same form as the param
+/// file, so that the serialized value can be checked before submitting.
+pub fn config_param_preview(config_cell: &Cell, key_number: u32) -> Result<serde_json::Value, String> {
+    let mut slice = SliceData::load_cell(config_cell.clone())
+        .map_err(|e| format!("failed to load config param cell: {}", e))?;
+    let param = ConfigParamEnum::construct_from_slice_and_number(&mut slice, key_number)
+        .map_err(|e| format!("failed to deserialize config param {}: {}", key_number, e))?;
+    let mut config_params = ConfigParams::default();
+    config_params.set_config(param)
+        .map_err(|e| format!("failed to set config param {}: {}", key_number, e))?;
+    let param = ton_block_json::serialize_config_param(&config_params, key_number)
+        .map_err(|e| format!("failed to serialize config param {} to json: {}", key_number, e))?;
+    let param: serde_json::Value = serde_json::from_str(&param)
+        .map_err(|e| format!("failed to parse config param {} json: {}", key_number, e))?;
+    Ok(json!({ format!("p{}", key_number): param }))
+}
+
+// builds update config message for the param, seqno is used if the message is generated without
+// ABI of the config contract
+fn build_update_config_message(
+    abi: Option<&str>,
+    seqno: Option<u32>,
+    config_master_address: Vec<u8>,
+    private_key_of_config_account: &[u8],
+    config_cell: Cell,
+    key_number: u32,
+) -> Result<Message, String> {
+    let config_account = ton_types::AccountId::from_raw(config_master_address, 32*8);
+    match (abi, seqno) {
+        (Some(abi), _) => prepare_message_new_config_param_solidity(abi, config_cell, key_number, config_account, private_key_of_config_account),
+        (None, Some(seqno)) => prepare_message_new_config_param(config_cell, seqno, key_number, config_account, private_key_of_config_account),
+        (None, None) => Err(r#""seqno" is not specified"#.to_string()),
+    }
+}
+
+/// Estimates fees of the sequence of update config messages without sending them. Messages are
+/// executed one after another locally on the state of the config account, so that every next
+/// message is run on the state left by the previous one. Seqno is incremented for each message.
+pub async fn estimate_update_config(
+    config: &Config,
+    abi: Option<&str>,
+    seqno: Option<&str>,
+    config_master_file: &str,
+    new_param_files: Vec<&str>,
+) -> Result<(), String> {
+    let ton = create_client_verbose(config)?;
+    let (config_master_address, private_key_of_config_account) = load_config_master(config_master_file)?;
+    let config_address = format!("-1:{}", hex::encode(&config_master_address));
+    let mut seqno = if abi.is_none() { Some(parse_seqno(seqno)?) } else { None };
+    let bc_config = fee_blockchain_config(config)?;
+    let mut state = query_account_field(ton.clone(), &config_address, "boc").await?;
+
+    let mut steps = Vec::new();
+    let mut total = TransactionFees::default();
+    for (index, new_param_file) in new_param_files.iter().enumerate() {
+        let (config_cell, key_number) = load_new_config_param(new_param_file, None)?;
+        let message = build_update_config_message(
+            abi,
+            seqno,
+            config_master_address.clone(),
+            &private_key_of_config_account,
+            config_cell,
+            key_number,
+        )?;
+        let message = base64::encode(&message.write_to_bytes()
+            .map_err(|e| format!(r#"failed to serialize message": {}"#, e))?);
+        let result = execute_locally(ton.clone(), state, message, None, None, bc_config.clone(), None).await
+            .map_err(|e| format!("failed to emulate update config message for {}: {}", new_param_file, e))?;
+        total.in_msg_fwd_fee += result.fees.in_msg_fwd_fee;
+        total.storage_fee += result.fees.storage_fee;
+        total.gas_fee += result.fees.gas_fee;
+        total.out_msgs_fwd_fee += result.fees.out_msgs_fwd_fee;
+        total.total_account_fees += result.fees.total_account_fees;
+        total.total_output += result.fees.total_output;
+        steps.push(json!({
+            "step": index + 1,
+            "new_param_file": new_param_file,
+            "seqno": seqno,
+            "fees": fees_json(&result.fees),
+        }));
+        seqno = seqno.map(|seqno| seqno + 1);
+        state = result.account;
+    }
+
+    let result = json!({ "steps": steps, "total": fees_json(&total) });
+    if config.is_json {
+        println!("{:#}", result);
+    } else {
+        println!("Estimated fees: {:#}", result);
+    }
+    Ok(())
+}
+
+fn fees_json(fees: &TransactionFees) -> serde_json::Value {
+    json!({
+        "in_msg_fwd_fee": fees.in_msg_fwd_fee.to_string(),
+        "storage_fee": fees.storage_fee.to_string(),
+        "gas_fee": fees.gas_fee.to_string(),
+        "out_msgs_fwd_fee": fees.out_msgs_fwd_fee.to_string(),
+        "total_account_fees": fees.total_account_fees.to_string(),
+        "total_output": fees.total_output.to_string(),
+    })
+}
+
+// Minimal schema of the json representation of the config param, which is used to report missing
+// or malformed fields of the commonly changed params before they are parsed.
+enum FieldKind {
+    Integer,
+    Bool,
+    Array,
+    Object(&'static [(&'static str, FieldKind)]),
+    ArrayOf(&'static [(&'static str, FieldKind)]),
+}
+
+const GAS_LIMITS_PRICES: &[(&str, FieldKind)] = &[
+    ("gas_price", FieldKind::Integer),
+    ("gas_limit", FieldKind::Integer),
+    ("special_gas_limit", FieldKind::Integer),
+    ("gas_credit", FieldKind::Integer),
+    ("block_gas_limit", FieldKind::Integer),
+    ("freeze_due_limit", FieldKind::Integer),
+    ("delete_due_limit", FieldKind::Integer),
+    ("flat_gas_limit", FieldKind::Integer),
+    ("flat_gas_price", FieldKind::Integer),
+];
+
+const PARAM_LIMITS: &[(&str, FieldKind)] = &[
+    ("underload", FieldKind::Integer),
+    ("soft_limit", FieldKind::Integer),
+    ("hard_limit", FieldKind::Integer),
+];
+
+const BLOCK_LIMITS: &[(&str, FieldKind)] = &[
+    ("bytes", FieldKind::Object(PARAM_LIMITS)),
+    ("gas", FieldKind::Object(PARAM_LIMITS)),
+    ("lt_delta", FieldKind::Object(PARAM_LIMITS)),
+];
+
+const MSG_FORWARD_PRICES: &[(&str, FieldKind)] = &[
+    ("lump_price", FieldKind::Integer),
+    ("bit_price", FieldKind::Integer),
+    ("cell_price", FieldKind::Integer),
+    ("ihr_price_factor", FieldKind::Integer),
+    ("first_frac", FieldKind::Integer),
+    ("next_frac", FieldKind::Integer),
+];
+
+const CONFIG_PARAM_SCHEMAS: &[(u32, FieldKind)] = &[
+    (8, FieldKind::Object(&[
+        ("version", FieldKind::Integer),
+        ("capabilities", FieldKind::Integer),
+    ])),
+    (9, FieldKind::Array),
+    (10, FieldKind::Array),
+    (14, FieldKind::Object(&[
+        ("masterchain_block_fee", FieldKind::Integer),
+        ("basechain_block_fee", FieldKind::Integer),
+    ])),
+    (15, FieldKind::Object(&[
+        ("validators_elected_for", FieldKind::Integer),
+        ("elections_start_before", FieldKind::Integer),
+        ("elections_end_before", FieldKind::Integer),
+        ("stake_held_for", FieldKind::Integer),
+    ])),
+    (16, FieldKind::Object(&[
+        ("max_validators", FieldKind::Integer),
+        ("max_main_validators", FieldKind::Integer),
+        ("min_validators", FieldKind::Integer),
+    ])),
+    (17, FieldKind::Object(&[
+        ("min_stake", FieldKind::Integer),
+        ("max_stake", FieldKind::Integer),
+        ("min_total_stake", FieldKind::Integer),
+        ("max_stake_factor", FieldKind::Integer),
+    ])),
+    (18, FieldKind::ArrayOf(&[
+        ("utime_since", FieldKind::Integer),
+        ("bit_price_ps", FieldKind::Integer),
+        ("cell_price_ps", FieldKind::Integer),
+        ("mc_bit_price_ps", FieldKind::Integer),
+        ("mc_cell_price_ps", FieldKind::Integer),
+    ])),
+    (20, FieldKind::Object(GAS_LIMITS_PRICES)),
+    (21, FieldKind::Object(GAS_LIMITS_PRICES)),
+    (22, FieldKind::Object(BLOCK_LIMITS)),
+    (23, FieldKind::Object(BLOCK_LIMITS)),
+    (24, FieldKind::Object(MSG_FORWARD_PRICES)),
+    (25, FieldKind::Object(MSG_FORWARD_PRICES)),
+    (28, FieldKind::Object(&[
+        ("shuffle_mc_validators", FieldKind::Bool),
+        ("mc_catchain_lifetime", FieldKind::Integer),
+        ("shard_catchain_lifetime", FieldKind::Integer),
+        ("shard_validators_lifetime", FieldKind::Integer),
+        ("shard_validators_num", FieldKind::Integer),
+    ])),
+    (29, FieldKind::Object(&[
+        ("new_catchain_ids", FieldKind::Bool),
+        ("round_candidates", FieldKind::Integer),
+        ("next_candidate_delay_ms", FieldKind::Integer),
+        ("consensus_timeout_ms", FieldKind::Integer),
+        ("fast_attempts", FieldKind::Integer),
+        ("attempt_duration", FieldKind::Integer),
+        ("catchain_max_deps", FieldKind::Integer),
+        ("max_block_bytes", FieldKind::Integer),
+        ("max_collated_bytes", FieldKind::Integer),
+    ])),
+    (31, FieldKind::Array),
+];
+
+/// Checks that the json of the config param has all required fields of the expected types, so that
+/// the error points to the exact field. Params without known schema are not checked.
+pub fn validate_config_param(key_number: u32, param: &serde_json::Value) -> Result<(), String> {
+    match CONFIG_PARAM_SCHEMAS.iter().find(|(number, _)| *number == key_number) {
+        Some((_, kind)) => check_config_field(&format!("p{}", key_number), param, kind),
+        None => Ok(()),
+    }
+}
+
+fn check_config_field(path: &str, value: &serde_json::Value, kind: &FieldKind) -> Result<(), String> {
+    let expected = match kind {
+        FieldKind::Integer => match value {
+            serde_json::Value::Number(number) if number.is_u64() || number.is_i64() => return Ok(()),
+            serde_json::Value::String(string) if is_integer_string(string) => return Ok(()),
+            _ => "integer",
+        },
+        FieldKind::Bool if value.is_boolean() => return Ok(()),
+        FieldKind::Bool => "boolean",
+        FieldKind::Array if value.is_array() => return Ok(()),
+        FieldKind::Array => "array",
+        FieldKind::Object(fields) => match value.as_object() {
+            Some(object) => {
+                for (name, kind) in fields.iter() {
+                    let field = object.get(*name)
+                        .ok_or(format!(r#"{}: required field "{}" is missing"#, path, name))?;
+                    check_config_field(&format!("{}.{}", path, name), field, kind)?;
+                }
+                return Ok(());
+            },
+            None => "object",
+        },
+        FieldKind::ArrayOf(fields) => match value.as_array() {
+            Some(array) => {
+                for (i, item) in array.iter().enumerate() {
+                    check_config_field(&format!("{}[{}]", path, i), item, &FieldKind::Object(fields))?;
+                }
+                return Ok(());
+            },
+            None => "array",
+        },
+    };
+    Err(format!("{}: expected {}, found {}", path, expected, value))
+}
+
+// integers can be specified as decimal or hex (with `0x` prefix) strings
+fn is_integer_string(string: &str) -> bool {
+    match string.strip_prefix("0x") {
+        Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => !string.is_empty() && string.chars().all(|c| c.is_ascii_digit()),
+    }
+}
+
 pub fn serialize_config_param(config_str: String) -> Result<(Cell, u32), String> {
     let config_json: serde_json::Value = serde_json::from_str(&*config_str)
         .map_err(|e| format!(r#"failed to parse "new_param_file": {}"#, e))?;
@@ -381,6 +676,11 @@ pub fn serialize_config_param(config_str: String) -> Result<(Cell, u32), String>
         .parse::<u32>()
         .map_err(|e| format!(r#""new_param_file" is not a valid json: {}"#, e))?;
 
+    if let Some(param) = config_json.values().next() {
+        validate_config_param(key_number, param)
+            .map_err(|e| format!(r#"invalid config param in "new_param_file": {}"#, e))?;
+    }
+
     let config_params = ton_block_json::parse_config(config_json)
         .map_err(|e| format!(r#"failed to parse config params from "new_param_file": {}"#, e))?;
 
@@ -391,11 +691,31 @@ pub fn serialize_config_param(config_str: String) -> Result<(Cell, u32), String>
     let mut cell = BuilderData::default();
     config_param.write_to_cell(&mut cell)
         .map_err(|e| format!(r#"failed to serialize config param": {}"#, e))?;
-    let config_cell = cell.references()[0].clone();
+    let config_cell = config_param_cell(&cell, key_number)?;
 
     Ok((config_cell, key_number))
 }
 
+/// Loads config param cell from base64 encoded boc and checks that it can be deserialized
+/// as config param with the given number.
+pub fn config_param_from_boc(boc: &str, key_number: u32) -> Result<Cell, String> {
+    let bytes = base64::decode(boc.trim())
+        .map_err(|e| format!("failed to decode config param boc from base64: {}", e))?;
+    let cell = ton_types::cells_serialization::deserialize_tree_of_cells(&mut std::io::Cursor::new(&bytes))
+        .map_err(|e| format!("failed to deserialize config param boc: {}", e))?;
+    let mut slice = SliceData::load_cell(cell.clone())
+        .map_err(|e| format!("failed to load config param cell: {}", e))?;
+    ConfigParamEnum::construct_from_slice_and_number(&mut slice, key_number)
+        .map_err(|e| format!("cell is not a valid config param {}: {}", key_number, e))?;
+    Ok(cell)
+}
+
+// serialized config param is stored in the first reference of the builder
+fn config_param_cell(cell: &BuilderData, key_number: u32) -> Result<Cell, String> {
+    cell.references().first().cloned()
+        .ok_or(format!("failed to serialize config param {}: serialized data doesn't contain a reference to the param cell", key_number))
+}
+
 fn prepare_message_new_config_param(
     config_param: Cell,
     seqno: u32,
@@ -403,40 +723,131 @@ fn prepare_message_new_config_param(
     config_account: SliceData,
     private_key_of_config_account: &[u8]
 ) -> Result<Message, String> {
-    let prefix = hex::decode(PREFIX_UPDATE_CONFIG_MESSAGE_DATA).unwrap();
-    let since_the_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as u32 + 100; // timestamp + 100 secs
+    let prefix = hex::decode(PREFIX_UPDATE_CONFIG_MESSAGE_DATA)
+        .map_err(|e| format!("failed to decode update config message prefix: {}", e))?;
+    let since_the_epoch = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("failed to obtain system time: {}", e))?
+        .as_secs() as u32 + 100; // timestamp + 100 secs
 
-    let mut cell = BuilderData::default();
-    cell.append_raw(prefix.as_slice(), 32).unwrap();
-    cell.append_u32(seqno).unwrap();
-    cell.append_u32(since_the_epoch).unwrap();
-    cell.append_i32(key_number as i32).unwrap();
-    cell.checked_append_reference(config_param.clone()).unwrap();
+    let data = update_config_message_data(&prefix, seqno, since_the_epoch, key_number, config_param)?;
 
-    let secret = SecretKey::from_bytes(private_key_of_config_account)
-        .map_err(|e| format!(r#"failed to read private key from config-master file": {}"#, e))?;
-    let public = PublicKey::from(&secret);
-    let keypair = Keypair { secret, public };
-        
-    let msg_signature = keypair.sign(cell.finalize(0).unwrap().repr_hash().as_slice()).to_bytes();
+    let msg_signature = sign_cell(
+        &data.clone().finalize(0)
+            .map_err(|e| format!("failed to finalize update config message data: {}", e))?,
+        private_key_of_config_account,
+    )?;
 
     let mut cell = BuilderData::default();
-    cell.append_raw(&msg_signature, 64*8).unwrap();
-    cell.append_raw(prefix.as_slice(), 32).unwrap();
-    cell.append_u32(seqno).unwrap();
-    cell.append_u32(since_the_epoch).unwrap();
-    cell.append_i32(key_number as i32).unwrap();
-    cell.checked_append_reference(config_param).unwrap();
-
-    let config_contract_address = MsgAddressInt::with_standart(None, -1, config_account).unwrap();
+    cell.append_raw(&msg_signature, 64*8)
+        .map_err(|e| format!("failed to append signature to update config message: {}", e))?;
+    cell.append_builder(&data)
+        .map_err(|e| format!("failed to append data to update config message: {}", e))?;
+
+    let config_contract_address = MsgAddressInt::with_standart(None, -1, config_account)
+        .map_err(|e| format!("failed to construct config contract address: {}", e))?;
     let mut header = ExternalInboundMessageHeader::new(AddrNone, config_contract_address);
     header.import_fee = Grams::zero();
-    let body = SliceData::load_builder(cell).unwrap();
+    let body = SliceData::load_builder(cell)
+        .map_err(|e| format!("failed to load update config message body: {}", e))?;
     let message = Message::with_ext_in_header_and_body(header, body);
 
     Ok(message)
 }
 
+/// Signs representation hash of the cell with the ed25519 secret key (e.g. the config-master private key).
+pub fn sign_cell(cell: &Cell, secret_key: &[u8]) -> Result<[u8; 64], String> {
+    let secret = SecretKey::from_bytes(secret_key)
+        .map_err(|e| format!(r#"failed to read private key from config-master file": {}"#, e))?;
+    let public = PublicKey::from(&secret);
+    let keypair = Keypair { secret, public };
+    Ok(keypair.sign(cell.repr_hash().as_slice()).to_bytes())
+}
+
+// builds the signed part of the update config message: prefix, seqno, valid since time, param index and param cell
+fn update_config_message_data(
+    prefix: &[u8],
+    seqno: u32,
+    since_the_epoch: u32,
+    key_number: u32,
+    config_param: Cell,
+) -> Result<BuilderData, String> {
+    let mut cell = BuilderData::default();
+    cell.append_raw(prefix, 32)
+        .and_then(|cell| cell.append_u32(seqno))
+        .and_then(|cell| cell.append_u32(since_the_epoch))
+        .and_then(|cell| cell.append_i32(key_number as i32))
+        .and_then(|cell| cell.checked_append_reference(config_param))
+        .map_err(|e| format!("failed to build update config message data: {}", e))?;
+    Ok(cell)
+}
+
+pub struct UpdateConfigMessageInfo {
+    pub is_valid: bool,
+    pub seqno: u32,
+    pub valid_until: u32,
+    pub key_number: u32,
+    pub config_param: Cell,
+}
+
+/// Checks signature of the update config message generated by `prepare_message_new_config_param`.
+/// The signed part of the message is rebuilt from the fields recovered from the message body.
+fn verify_message_new_config_param(message: &Message, public_key: &[u8]) -> Result<UpdateConfigMessageInfo, String> {
+    let public = PublicKey::from_bytes(public_key)
+        .map_err(|e| format!("failed to read public key of config account: {}", e))?;
+    let mut body = message.body()
+        .ok_or("update config message has no body".to_string())?;
+    let signature = body.get_next_bytes(64)
+        .map_err(|e| format!("failed to read signature from update config message: {}", e))?;
+    let signature = Signature::from_bytes(&signature)
+        .map_err(|e| format!("failed to read signature from update config message: {}", e))?;
+    let prefix = body.get_next_bytes(4)
+        .map_err(|e| format!("failed to read prefix from update config message: {}", e))?;
+    if hex::encode(&prefix) != PREFIX_UPDATE_CONFIG_MESSAGE_DATA {
+        return Err(format!("message is not an update config message: unexpected prefix {}", hex::encode(&prefix)));
+    }
+    let seqno = body.get_next_u32()
+        .map_err(|e| format!("failed to read seqno from update config message: {}", e))?;
+    let valid_until = body.get_next_u32()
+        .map_err(|e| format!("failed to read valid until time from update config message: {}", e))?;
+    let key_number = body.get_next_i32()
+        .map_err(|e| format!("failed to read param index from update config message: {}", e))? as u32;
+    let config_param = body.checked_drain_reference()
+        .map_err(|e| format!("failed to read param cell from update config message: {}", e))?;
+
+    let data = update_config_message_data(&prefix, seqno, valid_until, key_number, config_param.clone())?;
+    let hash = data.finalize(0)
+        .map_err(|e| format!("failed to finalize update config message data: {}", e))?
+        .repr_hash();
+    let is_valid = public.verify(hash.as_slice(), &signature).is_ok();
+
+    Ok(UpdateConfigMessageInfo { is_valid, seqno, valid_until, key_number, config_param })
+}
+
+pub fn verify_update_config_message(message: &str, public_key: &str, is_json: bool) -> Result<(), String> {
+    let message = hex::decode(message)
+        .map_err(|e| format!("failed to decode message from hex: {}", e))?;
+    let message = Message::construct_from_bytes(&message)
+        .map_err(|e| format!("failed to deserialize message: {}", e))?;
+    let public_key = hex::decode(public_key)
+        .map_err(|e| format!("failed to decode public key from hex: {}", e))?;
+
+    let info = verify_message_new_config_param(&message, &public_key)?;
+    let result = json!({
+        "valid": info.is_valid,
+        "seqno": info.seqno,
+        "valid_until": info.valid_until,
+        "index": info.key_number,
+        "param_cell_hash": info.config_param.repr_hash().to_hex_string(),
+    });
+    if is_json {
+        println!("{:#}", result);
+    } else {
+        println!("Signature is {}.", if info.is_valid { "valid" } else { "INVALID" });
+        println!("Recovered fields: {:#}", result);
+    }
+    Ok(())
+}
+
 fn prepare_message_new_config_param_solidity(
     abi: &str,
     config_param: Cell,
@@ -449,8 +860,11 @@ fn prepare_message_new_config_param_solidity(
     let public = PublicKey::from(&secret);
     let keypair = Keypair { secret, public };
     
-    let config_contract_address = MsgAddressInt::with_standart(None, -1, config_account).unwrap();
-    let since_the_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_micros() as u64;
+    let config_contract_address = MsgAddressInt::with_standart(None, -1, config_account)
+        .map_err(|e| format!("failed to construct config contract address: {}", e))?;
+    let since_the_epoch = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("failed to obtain system time: {}", e))?
+        .as_micros() as u64;
 
     let header = [("time".to_owned(), TokenValue::Time(since_the_epoch))]
         .into_iter()
@@ -523,3 +937,111 @@ pub async fn dump_blockchain_config(config: &Config, path: &str) -> Result<(), S
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_param_cell() {
+        let cell = BuilderData::default();
+        assert!(config_param_cell(&cell, 34).is_err());
+
+        let mut cell = BuilderData::default();
+        cell.append_u32(34).unwrap();
+        assert!(config_param_cell(&cell, 34).is_err());
+
+        let mut cell = BuilderData::default();
+        cell.checked_append_reference(Cell::default()).unwrap();
+        assert_eq!(config_param_cell(&cell, 34).unwrap(), Cell::default());
+    }
+
+    #[test]
+    fn test_config_param_preview() {
+        let param = json!({ "p8": { "version": 10, "capabilities": 8238 } }).to_string();
+        let (cell, key_number) = serialize_config_param(param).unwrap();
+        let preview = config_param_preview(&cell, key_number).unwrap();
+        assert_eq!(preview["p8"]["version"], json!(10));
+        let (cell_again, _) = serialize_config_param(preview.to_string()).unwrap();
+        assert_eq!(cell_again, cell);
+    }
+
+    #[test]
+    fn test_validate_config_param() {
+        assert!(validate_config_param(8, &json!({ "version": 10, "capabilities": "0x202e" })).is_ok());
+        assert_eq!(
+            validate_config_param(8, &json!({ "version": 10 })).unwrap_err(),
+            r#"p8: required field "capabilities" is missing"#,
+        );
+        assert_eq!(
+            validate_config_param(16, &json!({ "max_validators": "many", "max_main_validators": 100, "min_validators": 13 }))
+                .unwrap_err(),
+            r#"p16.max_validators: expected integer, found "many""#,
+        );
+        let limits = json!({ "underload": 1, "soft_limit": 2, "hard_limit": 3 });
+        let param = json!({ "bytes": limits, "gas": limits, "lt_delta": { "underload": 1, "soft_limit": 2 } });
+        assert_eq!(
+            validate_config_param(22, &param).unwrap_err(),
+            r#"p22.lt_delta: required field "hard_limit" is missing"#,
+        );
+        let param = json!([{ "utime_since": 0, "bit_price_ps": 1, "cell_price_ps": 500, "mc_bit_price_ps": 1000, "mc_cell_price_ps": 1.5 }]);
+        assert_eq!(
+            validate_config_param(18, &param).unwrap_err(),
+            "p18[0].mc_cell_price_ps: expected integer, found 1.5",
+        );
+        assert!(validate_config_param(28, &json!({ "shuffle_mc_validators": 1 })).unwrap_err().contains("expected boolean"));
+        assert!(validate_config_param(10, &json!({})).unwrap_err().contains("expected array"));
+        // params without schema are not checked here
+        assert!(validate_config_param(100, &json!("anything")).is_ok());
+
+        let param = json!({ "p8": { "version": 10 } }).to_string();
+        assert!(serialize_config_param(param).unwrap_err().contains(r#"required field "capabilities" is missing"#));
+    }
+
+    #[test]
+    fn test_verify_message_new_config_param() {
+        let secret = [1u8; 32];
+        let public = PublicKey::from(&SecretKey::from_bytes(&secret).unwrap());
+        let config_account = ton_types::AccountId::from_raw(vec![0x55; 32], 32*8);
+        let message = prepare_message_new_config_param(
+            Cell::default(), 5, 34, config_account, &secret
+        ).unwrap();
+
+        let info = verify_message_new_config_param(&message, public.as_bytes()).unwrap();
+        assert!(info.is_valid);
+        assert_eq!(info.seqno, 5);
+        assert_eq!(info.key_number, 34);
+        assert_eq!(info.config_param, Cell::default());
+
+        let other = PublicKey::from(&SecretKey::from_bytes(&[2u8; 32]).unwrap());
+        let info = verify_message_new_config_param(&message, other.as_bytes()).unwrap();
+        assert!(!info.is_valid);
+    }
+
+    #[test]
+    fn test_config_param_from_boc() {
+        let (cell, key_number) = serialize_config_param(
+            r#"{"p8": {"version": 10, "capabilities": 8238}}"#.to_string()
+        ).unwrap();
+        let boc = base64::encode(&ton_types::cells_serialization::serialize_toc(&cell).unwrap());
+        assert_eq!(config_param_from_boc(&boc, key_number).unwrap(), cell);
+        assert!(config_param_from_boc(&boc, 34).is_err());
+        assert!(config_param_from_boc("not a boc", 8).is_err());
+    }
+
+    #[test]
+    fn test_sign_cell() {
+        let signature = sign_cell(&Cell::default(), &[1u8; 32]).unwrap();
+        assert_eq!(
+            hex::encode(signature),
+            "d21a6eb0e959dad850d8459e0083ca56931956c2d1f7222b74ffdd0f14e1c7a7\
+             2bb935ecbc5cd34836b7551671ba91c038fbd5394f73e5471b69352c62b53702"
+        );
+        let public = PublicKey::from(&SecretKey::from_bytes(&[1u8; 32]).unwrap());
+        assert_eq!(
+            hex::encode(public.as_bytes()),
+            "8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c"
+        );
+        assert!(sign_cell(&Cell::default(), &[1u8; 31]).is_err());
+    }
+}
@@ -14,8 +14,9 @@ use std::env;
 use std::path::PathBuf;
 use crate::config::{Config, LOCALNET};
 
-use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use lazy_static::lazy_static;
 use ton_client::abi::{
     Abi, AbiConfig, AbiContract, DecodedMessageBody, DeploySet, ParamsOfDecodeMessageBody,
     ParamsOfEncodeMessage, Signer,
@@ -24,7 +25,7 @@ use ton_client::crypto::{CryptoConfig, KeyPair};
 use ton_client::error::ClientError;
 use ton_client::net::{query_collection, OrderBy, ParamsOfQueryCollection, NetworkConfig};
 use ton_client::{ClientConfig, ClientContext};
-use ton_block::{Account, MsgAddressInt, Deserializable, CurrencyCollection, StateInit, Serializable};
+use ton_block::{Account, MsgAddressInt, MsgAddressExt, Deserializable, CurrencyCollection, StateInit, Serializable};
 use std::str::FromStr;
 use clap::ArgMatches;
 use serde_json::{Value, json};
@@ -42,6 +43,7 @@ pub const HD_PATH: &str = "m/44'/396'/0'/0/0";
 pub const WORD_COUNT: u8 = 12;
 
 pub const SDK_EXECUTION_ERROR_CODE: u32 = 414;
+pub const SDK_LOW_BALANCE_ERROR_CODE: u32 = 407;
 const CONFIG_BASE_NAME: &str = "tonos-cli.conf.json";
 const GLOBAL_CONFIG_PATH: &str = ".tonos-cli.global.conf.json";
 
@@ -137,8 +139,84 @@ pub fn get_server_endpoints(config: &Config) -> Vec<String> {
     }).collect::<Vec<String>>()
 }
 
+lazy_static! {
+    // endpoints ordered by latency, computed once per session for the same list of endpoints:
+    // the list which was ordered and the result
+    static ref ORDERED_ENDPOINTS: Mutex<Option<(Vec<String>, Vec<String>)>> = Mutex::new(None);
+}
+
+// Endpoint which doesn't respond in this time is considered unavailable while choosing the fastest one.
+const ENDPOINT_PING_TIMEOUT_MS: u64 = 500;
+
+async fn ping_endpoint(endpoint: String, timeout: u64) -> Option<(Duration, String)> {
+    let url = if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+        format!("{}/graphql?query=%7Binfo%7Bversion%7D%7D", endpoint)
+    } else {
+        format!("https://{}/graphql?query=%7Binfo%7Bversion%7D%7D", endpoint)
+    };
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout))
+        .build()
+        .ok()?;
+    let start = Instant::now();
+    let response = client.get(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    Some((start.elapsed(), endpoint))
+}
+
+// Moves the fastest-responding endpoint to the beginning of the list.
+// Endpoints are returned in the configured order if none of them responded.
+fn order_endpoints_by_latency(endpoints: Vec<String>, config: &Config) -> Vec<String> {
+    if !config.fastest_endpoint || endpoints.len() < 2 {
+        return endpoints;
+    }
+    if let Some((cached, ordered)) = ORDERED_ENDPOINTS.lock().unwrap().as_ref() {
+        if *cached == endpoints {
+            return ordered.clone();
+        }
+    }
+    let to_ping = endpoints.clone();
+    // client is created in sync context, so endpoints are pinged in a separate runtime
+    let pinging = std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().ok()?;
+        runtime.block_on(async move {
+            let pings = to_ping.into_iter().map(|endpoint| ping_endpoint(endpoint, ENDPOINT_PING_TIMEOUT_MS));
+            futures::future::join_all(pings).await
+                .into_iter()
+                .flatten()
+                .min_by_key(|(latency, _)| *latency)
+        })
+    });
+    // waiting must not block the worker of the current runtime, so that other tasks keep running
+    let in_multi_thread_runtime = tokio::runtime::Handle::try_current()
+        .map(|handle| handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread)
+        .unwrap_or(false);
+    let fastest = if in_multi_thread_runtime {
+        tokio::task::block_in_place(|| pinging.join())
+    } else {
+        pinging.join()
+    }.ok().flatten();
+
+    let cached = endpoints.clone();
+    let ordered = match fastest {
+        Some((latency, fastest)) => {
+            if !config.is_json {
+                println!("Fastest endpoint: {} ({} ms)", fastest, latency.as_millis());
+            }
+            let mut ordered = vec![fastest.clone()];
+            ordered.extend(endpoints.into_iter().filter(|endpoint| *endpoint != fastest));
+            ordered
+        },
+        None => endpoints,
+    };
+    *ORDERED_ENDPOINTS.lock().unwrap() = Some((cached, ordered.clone()));
+    ordered
+}
+
 pub fn create_client(config: &Config) -> Result<TonClient, String> {
-    let modified_endpoints = get_server_endpoints(config);
+    let modified_endpoints = order_endpoints_by_latency(get_server_endpoints(config), config);
     if !config.is_json {
         println!("Connecting to:\n\tUrl: {}", config.url);
         println!("\tEndpoints: {:?}\n", modified_endpoints);
@@ -552,6 +630,15 @@ pub fn print_account(
     }
 }
 
+/// Parses external address in form `:<hex>`, which can be a source of external inbound messages.
+pub fn parse_ext_address(address: &str) -> Result<MsgAddressExt, String> {
+    match MsgAddressExt::from_str(address) {
+        Ok(address @ MsgAddressExt::AddrExtern(_)) => Ok(address),
+        Ok(_) => Err(format!("{} is not an external address", address)),
+        Err(e) => Err(format!("invalid external address {}: {}", address, e)),
+    }
+}
+
 pub fn construct_account_from_tvc(tvc_path: &str, address: Option<&str>, balance: Option<u64>) -> Result<Account, String> {
     Account::active_by_init_code_hash(
         match address {
@@ -727,9 +814,8 @@ pub fn contract_data_from_matches_or_config_alias(
     Ok((address, Some(abi), keys))
 }
 
-pub fn blockchain_config_from_default_json() -> Result<BlockchainConfig, String> {
-    // Default config params from evernode-se https://github.com/tonlabs/evernode-se/blob/master/docker/ton-node/blockchain.conf.json
-    let json = r#"{
+// Default config params from evernode-se https://github.com/tonlabs/evernode-se/blob/master/docker/ton-node/blockchain.conf.json
+const DEFAULT_CONFIG_JSON: &str = r#"{
   "p0": "5555555555555555555555555555555555555555555555555555555555555555",
   "p1": "3333333333333333333333333333333333333333333333333333333333333333",
   "p2": "0000000000000000000000000000000000000000000000000000000000000000",
@@ -1006,7 +1092,9 @@ pub fn blockchain_config_from_default_json() -> Result<BlockchainConfig, String>
     ]
   }
 }"#;
-    let map = serde_json::from_str::<serde_json::Map<String, Value>>(&json)
+
+pub fn blockchain_config_from_default_json() -> Result<BlockchainConfig, String> {
+    let map = serde_json::from_str::<serde_json::Map<String, Value>>(DEFAULT_CONFIG_JSON)
         .map_err(|e| format!("Failed to parse config params as json: {e}"))?;
     let config_params = ton_block_json::parse_config(&map)
         .map_err(|e| format!("Failed to parse config params: {e}"))?;
@@ -1014,11 +1102,69 @@ pub fn blockchain_config_from_default_json() -> Result<BlockchainConfig, String>
         .map_err(|e| format!("Failed to construct default config: {e}"))
 }
 
-// loads blockchain config from the config contract boc, if it is none tries to load config contract
-// from the network, if it is unavailable returns default.
+// fields of the fee config and the default config params they override
+const FEE_CONFIG_FIELDS: [(&str, &str); 5] = [
+    ("gas_price", "p21"),
+    ("flat_gas_price", "p21"),
+    ("lump_price", "p25"),
+    ("bit_price", "p25"),
+    ("cell_price", "p25"),
+];
+
+/// Builds synthetic blockchain config for local emulation from the default config params
+/// (which match current mainnet prices for basechain) with gas and forward fee prices
+/// overridden by the fee config JSON, e.g. `{"gas_price": 65536000, "lump_price": 1000000}`.
+/// Returns base64 encoded boc of the config params.
+pub fn synthetic_blockchain_config(fee_config: &str) -> Result<String, String> {
+    let fee_config: Value = serde_json::from_str(fee_config)
+        .map_err(|e| format!("failed to parse fee config: {}", e))?;
+    let fee_config = fee_config.as_object()
+        .ok_or("fee config must be a JSON object".to_string())?;
+    let mut map = serde_json::from_str::<serde_json::Map<String, Value>>(DEFAULT_CONFIG_JSON)
+        .map_err(|e| format!("Failed to parse config params as json: {e}"))?;
+    for (name, value) in fee_config {
+        let (_, param) = FEE_CONFIG_FIELDS.iter()
+            .find(|(field, _)| field == name)
+            .ok_or(format!(r#"unknown fee config field "{}""#, name))?;
+        let value = match value {
+            Value::Number(number) if number.is_u64() => number.to_string(),
+            Value::String(string) if string.parse::<u64>().is_ok() => string.clone(),
+            _ => return Err(format!(r#"fee config field "{}" must be an unsigned integer"#, name)),
+        };
+        map[*param][name] = json!(value);
+    }
+    let config_params = ton_block_json::parse_config(&map)
+        .map_err(|e| format!("Failed to parse config params: {e}"))?;
+    let cell = config_params.serialize()
+        .map_err(|e| format!("failed to serialize config params: {}", e))?;
+    let bytes = ton_types::cells_serialization::serialize_toc(&cell)
+        .map_err(|e| format!("failed to serialize config params cell: {}", e))?;
+    Ok(base64::encode(&bytes))
+}
+
+// returns synthetic blockchain config if fee config is set
+pub fn fee_blockchain_config(config: &Config) -> Result<Option<String>, String> {
+    config.fee_config.as_deref().map(synthetic_blockchain_config).transpose()
+}
+
+// loads blockchain config from the json file with config params (e.g. `{"p20": {...}, ...}`)
+fn blockchain_config_from_json_file(config_path: &str) -> Result<BlockchainConfig, String> {
+    let json = std::fs::read_to_string(config_path)
+        .map_err(|e| format!("Failed to read config params from file {config_path}: {e}"))?;
+    let map = serde_json::from_str::<serde_json::Map<String, Value>>(&json)
+        .map_err(|e| format!("Failed to parse config params from file {config_path} as json: {e}"))?;
+    let config_params = ton_block_json::parse_config(&map)
+        .map_err(|e| format!("Failed to parse config params from file {config_path}: {e}"))?;
+    BlockchainConfig::with_config(config_params)
+        .map_err(|e| format!("Failed to construct config from file {config_path}: {e}"))
+}
+
+// loads blockchain config from the config contract boc (or json file with config params), if it is
+// none tries to load config contract from the network, if it is unavailable returns default.
 pub async fn get_blockchain_config(cli_config: &Config, config_contract_boc_path: Option<&str>) ->
     Result<BlockchainConfig, String> {
     match config_contract_boc_path {
+        Some(config_path) if config_path.ends_with(".json") => blockchain_config_from_json_file(config_path),
         Some(config_path) => {
             let acc =Account::construct_from_file(config_path)
                 .map_err(|e| format!("Failed to load config contract account from file {config_path}: {e}"))?;
@@ -1028,7 +1174,7 @@ pub async fn get_blockchain_config(cli_config: &Config, config_contract_boc_path
             let ton_client = create_client(cli_config)?;
             let config = query_account_field(
                 ton_client.clone(),
-                CONFIG_ADDR,
+                cli_config.config_address.as_deref().unwrap_or(CONFIG_ADDR),
                 "boc",
             ).await;
             let config_account = config.and_then(|config|
@@ -35,14 +35,15 @@ mod replay;
 mod debug;
 mod run;
 mod message;
+mod metrics;
 #[cfg(feature = "sold")]
 mod compile;
 
-use account::{get_account, calc_storage, wait_for_change};
-use call::{call_contract, call_contract_with_msg};
+use account::{get_account, calc_storage, wait_for_change, watch_account};
+use call::{attach_comment, attach_value, call_contract, call_contract_with_result, CallOptions, check_call_result, check_code_hash, build_batch_params, InitState, compare_local_and_onchain, call_contract_with_msg, estimate_forward_fee, emulate_locally_offline, print_local_run_fees, send_queue, cancel_queued_message, parse_expire_at, apply_expire_at, parse_header_override, resolve_method};
 use clap::{ArgMatches, SubCommand, Arg, AppSettings, App};
 use config::{Config, set_config, clear_config};
-use crypto::{generate_mnemonic, extract_pubkey, generate_keypair};
+use crypto::{generate_mnemonic, extract_pubkey, generate_keypair, check_keypair, select_keyfile};
 use debot::{create_debot_command, debot_command};
 use decode::{create_decode_command, decode_command};
 use debug::{create_debug_command, debug_command};
@@ -60,7 +61,7 @@ use std::collections::BTreeMap;
 use std::env;
 use std::process::exit;
 use std::sync::Arc;
-use ton_client::abi::{ParamsOfEncodeMessageBody, CallSet};
+use ton_client::abi::{ParamsOfEncodeMessageBody, CallSet, FunctionHeader};
 use ton_types::deserialize_tree_of_cells_inmem;
 use voting::{create_proposal, decode_proposal, vote};
 use crate::account::dump_accounts;
@@ -68,9 +69,9 @@ use crate::account::dump_accounts;
 use crate::compile::{compile_command, create_compile_command};
 
 use crate::config::{FullConfig, resolve_net_name};
-use crate::getconfig::gen_update_config_message;
-use crate::helpers::{abi_from_matches_or_config, AccountSource, default_config_name, global_config_path, load_abi_from_tvc, load_params, parse_lifetime, unpack_alternative_params, wc_from_matches_or_config};
-use crate::message::generate_message;
+use crate::getconfig::{estimate_update_config, gen_update_config_message, verify_update_config_message};
+use crate::helpers::{abi_from_matches_or_config, AccountSource, default_config_name, get_blockchain_config, global_config_path, load_abi_from_tvc, load_params, parse_lifetime, unpack_alternative_params, wc_from_matches_or_config};
+use crate::message::{generate_message, refresh_message};
 use crate::run::{run_command, run_get_method};
 
 const DEF_MSG_LIFETIME: u32 = 30;
@@ -80,6 +81,7 @@ enum CallType {
     Call,
     Msg,
     Fee,
+    Compare,
 }
 
 enum DeployType {
@@ -129,6 +131,67 @@ async fn main_internal() -> Result <(), String> {
         .help("Function arguments. Must be a list of `--name value` pairs or a json string with all arguments.")
         .multiple(true);
 
+    let value_arg = Arg::with_name("VALUE")
+        .long("--value")
+        .takes_value(true)
+        .help("Amount of tokens attached to the call. It is converted to nanotons and passed to the function's `value` parameter, so it is applicable to wallet contracts (e.g. multisig `submitTransaction` or `sendTransaction`).");
+
+    let comment_arg = Arg::with_name("COMMENT")
+        .long("--comment")
+        .takes_value(true)
+        .help("Text comment which is attached as the standard comment cell (zero tag and UTF-8 text) to the function's `payload` parameter, so it is applicable to wallet contracts.");
+
+    let header_arg = Arg::with_name("HEADER")
+        .long("--header")
+        .takes_value(true)
+        .help("Function header override in json format, e.g. '{\"time\":1650000000000,\"expire\":1650000060,\"pubkey\":\"...\"}'. Can be specified with a filename, which contains json data. Absent fields are computed as usual.");
+
+    let now_arg = Arg::with_name("NOW")
+        .long("--now")
+        .takes_value(true)
+        .help("Unix time in seconds used as the block time for local run (`local_run` config option or fee command). If not specified, real time is used.");
+
+    let expire_at_arg = Arg::with_name("EXPIRE_AT")
+        .long("--expire_at")
+        .takes_value(true)
+        .help("Absolute expiration time of the message (unix time in seconds). Must be in the future. Is used instead of the lifetime.");
+
+    let emulate_balance_arg = Arg::with_name("EMULATE_BALANCE")
+        .long("--emulate_balance")
+        .takes_value(true)
+        .help("Account balance in nanotons which is used instead of the real one for local run (`local_run` config option or fee command).");
+
+    let expect_arg = Arg::with_name("EXPECT")
+        .long("--expect")
+        .takes_value(true)
+        .help("Expected result of the call in json format (or path to the file with it). The command fails if the decoded output doesn't contain the specified fields with the same values.");
+
+    let expect_exact_arg = Arg::with_name("EXPECT_EXACT")
+        .long("--expect_exact")
+        .requires("EXPECT")
+        .help("Requires the decoded output to match the expected result exactly, without extra fields.");
+
+    let key_label_arg = Arg::with_name("KEY_LABEL")
+        .long("--key_label")
+        .takes_value(true)
+        .help("If keys are specified as a directory, selects the key file with this name (without extension). If not specified, the key file named as the called address is used.");
+
+    let code_hash_arg = Arg::with_name("CODE_HASH")
+        .long("--code_hash")
+        .takes_value(true)
+        .help("Expected code hash of the called contract (hex string or path to the tvc file to take it from). The call is not made if the code hash of the account differs.");
+
+    let init_tvc_arg = Arg::with_name("INIT_TVC")
+        .long("--init_tvc")
+        .takes_value(true)
+        .help("Path to the tvc file with state init, which is attached to the message to deploy the uninitialized account along with the call. Address calculated from the state init must match the contract address.");
+
+    let init_pubkey_arg = Arg::with_name("INIT_PUBKEY")
+        .long("--init_pubkey")
+        .takes_value(true)
+        .requires("INIT_TVC")
+        .help("Public key (32 bytes in hex) which is set to the initial data of the state init specified with --init_tvc. If not specified, public key of the signing keys is used.");
+
     let author = "EverX";
 
     let callx_cmd = SubCommand::with_name("callx")
@@ -142,6 +205,21 @@ async fn main_internal() -> Result <(), String> {
         .arg(abi_arg.clone())
         .arg(keys_arg.clone())
         .arg(method_opt_arg.clone())
+        .arg(header_arg.clone())
+        .arg(expire_at_arg.clone())
+        .arg(now_arg.clone())
+        .arg(emulate_balance_arg.clone())
+        .arg(expect_arg.clone())
+        .arg(expect_exact_arg.clone())
+        .arg(code_hash_arg.clone())
+        .arg(key_label_arg.clone())
+        .arg(init_tvc_arg.clone())
+        .arg(init_pubkey_arg.clone())
+        .arg(Arg::with_name("BATCH")
+            .long("--batch")
+            .takes_value(true)
+            .conflicts_with("PARAMS")
+            .help("Json array of calls (or path to the file with it) in form [{\"method\": \"<name>\", \"params\": {...}}, ...]. Bodies of the calls are encoded with the contract ABI and passed as the single cell[] argument of the method."))
         .arg(multi_params_arg.clone());
 
     let tvc_arg = Arg::with_name("TVC")
@@ -266,7 +344,20 @@ async fn main_internal() -> Result <(), String> {
             .takes_value(true)
             .long("--phrase")
             .short("-p")
-            .help("Seed phrase (12 words) or secret (private) key. Seed phrase should be specified in quotes, secret key as 64 hex chars."));
+            .help("Seed phrase (12 words) or secret (private) key. Seed phrase should be specified in quotes, secret key as 64 hex chars."))
+        .arg(Arg::with_name("ENCRYPT")
+            .long("--encrypt")
+            .requires("KEY_FILE")
+            .help("Encrypts the keypair file with a passphrase. Passphrase is taken from TONOSCLI_KEYS_PASSPHRASE environment variable or requested from the terminal."));
+
+    let checkkeys_cmd = SubCommand::with_name("checkkeys")
+        .about("Checks that the keypair file is valid and prints its public key.")
+        .version(version_string)
+        .author(author)
+        .arg(Arg::with_name("KEY_FILE")
+            .takes_value(true)
+            .required(true)
+            .help("Path to the keypair file or seed phrase in quotes."));
 
     let genaddr_cmd = SubCommand::with_name("genaddr")
         .setting(AppSettings::AllowNegativeNumbers)
@@ -347,7 +438,32 @@ async fn main_internal() -> Result <(), String> {
         .arg(params_arg.clone())
         .arg(abi_arg.clone())
         .arg(keys_arg.clone())
-        .arg(sign_arg.clone());
+        .arg(sign_arg.clone())
+        .arg(value_arg.clone())
+        .arg(comment_arg.clone())
+        .arg(header_arg.clone())
+        .arg(expire_at_arg.clone())
+        .arg(now_arg.clone())
+        .arg(emulate_balance_arg.clone())
+        .arg(expect_arg.clone())
+        .arg(expect_exact_arg.clone())
+        .arg(code_hash_arg.clone())
+        .arg(key_label_arg.clone())
+        .arg(init_tvc_arg.clone())
+        .arg(init_pubkey_arg.clone());
+
+    let compare_cmd = SubCommand::with_name("compare")
+        .setting(AppSettings::AllowLeadingHyphen)
+        .about("Runs the function call locally and then on-chain and compares decoded outputs of both executions.")
+        .version(version_string)
+        .author(author)
+        .arg(address_arg.clone())
+        .arg(method_arg.clone())
+        .arg(params_arg.clone())
+        .arg(abi_arg.clone())
+        .arg(keys_arg.clone())
+        .arg(sign_arg.clone())
+        .arg(key_label_arg.clone());
 
     let send_cmd = SubCommand::with_name("send")
         .about("Sends a prepared message to the contract.")
@@ -359,6 +475,29 @@ async fn main_internal() -> Result <(), String> {
             .help("Message to send. Message data should be specified in quotes."))
         .arg(abi_arg.clone());
 
+    let send_queue_cmd = SubCommand::with_name("send-queue")
+        .about("Sends all messages from the queue file filled by message command with --queue option.")
+        .version(version_string)
+        .author(author)
+        .arg(Arg::with_name("QUEUE")
+            .required(true)
+            .takes_value(true)
+            .help("Path to the queue file."))
+        .arg(abi_arg.clone());
+
+    let cancel_message_cmd = SubCommand::with_name("cancel-message")
+        .about("Marks the message from the queue file as cancelled, so that send-queue command skips it. The message is not cancelled on-chain and can still be broadcast by others until it expires.")
+        .version(version_string)
+        .author(author)
+        .arg(Arg::with_name("QUEUE")
+            .required(true)
+            .takes_value(true)
+            .help("Path to the queue file."))
+        .arg(Arg::with_name("MESSAGE_ID")
+            .required(true)
+            .takes_value(true)
+            .help("Id of the message to cancel."));
+
     let message_cmd = SubCommand::with_name("message")
         .setting(AppSettings::AllowLeadingHyphen)
         .about("Generates a signed message with encoded function call.")
@@ -370,6 +509,10 @@ async fn main_internal() -> Result <(), String> {
         .arg(abi_arg.clone())
         .arg(keys_arg.clone())
         .arg(sign_arg.clone())
+        .arg(value_arg.clone())
+        .arg(comment_arg.clone())
+        .arg(key_label_arg.clone())
+        .arg(expire_at_arg.clone())
         .arg(Arg::with_name("LIFETIME")
             .long("--lifetime")
             .takes_value(true)
@@ -379,6 +522,33 @@ async fn main_internal() -> Result <(), String> {
             .takes_value(true)
             .help("Message creation time in milliseconds. If not specified, `now` is used."))
         .arg(output_arg.clone())
+        .arg(raw_arg.clone())
+        .arg(Arg::with_name("QUEUE")
+            .long("--queue")
+            .takes_value(true)
+            .conflicts_with_all(&["OUTPUT", "RAW"])
+            .help("Appends the message to the queue file instead of printing it. Messages from the queue can be sent with send-queue command."));
+
+    let refresh_message_cmd = SubCommand::with_name("refresh-message")
+        .about("Updates time and expire of the message generated by message command and signs it again.")
+        .version(version_string)
+        .author(author)
+        .arg(Arg::with_name("MESSAGE")
+            .required(true)
+            .takes_value(true)
+            .help("Message generated by message command (without --raw flag) or message in base64 boc."))
+        .arg(abi_arg.clone())
+        .arg(keys_arg.clone())
+        .arg(sign_arg.clone())
+        .arg(Arg::with_name("LIFETIME")
+            .long("--lifetime")
+            .takes_value(true)
+            .help("Period of time in seconds while message is valid."))
+        .arg(Arg::with_name("EDIT_PARAMS")
+            .long("--params")
+            .takes_value(true)
+            .help("Arguments of the function in json format (or a path to the file with them), which override the decoded ones."))
+        .arg(output_arg.clone())
         .arg(raw_arg.clone());
 
     let body_cmd = SubCommand::with_name("body")
@@ -483,6 +653,108 @@ async fn main_internal() -> Result <(), String> {
         .arg(Arg::with_name("OUT_OF_SYNC")
             .long("--out_of_sync")
             .help("Network connection \"out_of_sync_threshold\" parameter in seconds. Mind that it cant exceed half of the \"lifetime\" parameter."))
+        .arg(Arg::with_name("WAIT_BLOCKS")
+            .long("--wait_blocks")
+            .help("Number of masterchain blocks to wait for after the transaction is found."))
+        .arg(Arg::with_name("DUMMY_ACCOUNT")
+            .long("--dummy_account")
+            .help("Use a dummy account for local run if the account does not exist."))
+        .arg(Arg::with_name("RESULT_FILE")
+            .long("--result_file")
+            .help("Path to the file where results of call and run commands are saved."))
+        .arg(Arg::with_name("INCLUDE_BOC")
+            .long("--include_boc")
+            .help("Include base64 bocs of the transaction out messages in the call result."))
+        .arg(Arg::with_name("CHECK_INT_RANGE")
+            .long("--check_int_range")
+            .help("Check that integer arguments fit the width declared in the ABI."))
+        .arg(Arg::with_name("FASTEST_ENDPOINT")
+            .long("--fastest_endpoint")
+            .help("Ping endpoints and use the fastest one first."))
+        .arg(Arg::with_name("TREE_DEPTH")
+            .long("--tree_depth")
+            .help("Depth of the transaction tree which is fetched and decoded after call."))
+        .arg(Arg::with_name("MAX_MESSAGE_SIZE")
+            .long("--max_message_size")
+            .help("Maximum size of the encoded message boc which is checked before sending."))
+        .arg(Arg::with_name("FEE_CONFIG")
+            .long("--fee_config")
+            .help("Fee config used to build synthetic blockchain config for local emulation."))
+        .arg(Arg::with_name("REPLAY_PROTECTION")
+            .long("--replay_protection")
+            .help("Replay protection hint which is used to check call arguments."))
+        .arg(Arg::with_name("METRICS_FILE")
+            .long("--metrics_file")
+            .help("File where call metrics are written."))
+        .arg(Arg::with_name("RELAXED_JSON")
+            .long("--relaxed_json")
+            .help("Accept relaxed JSON for arguments of alternative syntax commands."))
+        .arg(Arg::with_name("FEES_ON_ABORT")
+            .long("--fees_on_abort")
+            .help("Return fees along with the abort reason when local run of the call fails during fee estimation."))
+        .arg(Arg::with_name("INCLUDE_BALANCE")
+            .long("--include_balance")
+            .help("Include balance of the account before and after the call in the call result."))
+        .arg(Arg::with_name("POLL_INTERVAL_MS")
+            .long("--poll_interval_ms")
+            .help("Interval in ms between checks while waiting for the transaction."))
+        .arg(Arg::with_name("DECODE_LAYOUT")
+            .long("--decode_layout")
+            .help("Annotate decoded call parameters with their ABI types and cell layout."))
+        .arg(Arg::with_name("CONFIG_ADDRESS")
+            .long("--config_address")
+            .help("Address of the config contract."))
+        .arg(Arg::with_name("ADDRESS_FORMAT")
+            .long("--address_format")
+            .help("Address format in the decoded output."))
+        .arg(Arg::with_name("UTC_TIME")
+            .long("--utc_time")
+            .help("Print times in UTC."))
+        .arg(Arg::with_name("CHECK_STORAGE_DUE")
+            .long("--check_storage_due")
+            .help("Check storage due of the called account."))
+        .arg(Arg::with_name("TABLE_OUTPUT")
+            .long("--table_output")
+            .help("Print array results as tables."))
+        .arg(Arg::with_name("STRICT_OUTPUT")
+            .long("--strict_output")
+            .help("Check decoded output against the ABI."))
+        .arg(Arg::with_name("STRUCTURED_TRACE")
+            .long("--structured_trace")
+            .help("Save structured trace on debug on fail."))
+        .arg(Arg::with_name("CHECK_FROZEN")
+            .long("--check_frozen")
+            .help("Check that the called account is not frozen."))
+        .arg(Arg::with_name("EMULATE_SRC")
+            .long("--emulate_src")
+            .help("Source address of external messages in local emulation."))
+        .arg(Arg::with_name("DUMP_ENCODE_PARAMS")
+            .long("--dump_encode_params")
+            .help("Dump message encoding parameters."))
+        .arg(Arg::with_name("WAIT_LEVEL")
+            .long("--wait_level")
+            .help("Level of the message processing to wait for."))
+        .arg(Arg::with_name("FEES_PERCENTAGE")
+            .long("--fees_percentage")
+            .help("Print fees as a percentage of the attached value."))
+        .arg(Arg::with_name("DEBUG_BC_CONFIG")
+            .long("--debug_bc_config")
+            .help("Blockchain config file for debug on fail."))
+        .arg(Arg::with_name("TOKEN_DECIMALS")
+            .long("--token_decimals")
+            .help("Number of token decimals for amounts with `T` suffix."))
+        .arg(Arg::with_name("ECHO_PARAMS")
+            .long("--echo_params")
+            .help("Print call arguments along with the result."))
+        .arg(Arg::with_name("UNLIMITED_BALANCE")
+            .long("--unlimited_balance")
+            .help("Use unlimited account balance for local run."))
+        .arg(Arg::with_name("CANONICAL_JSON")
+            .long("--canonical_json")
+            .help("Print results as canonical json."))
+        .arg(Arg::with_name("INCLUDE_TIMESTAMP")
+            .long("--include_timestamp")
+            .help("Include completion time in json call results."))
         .arg(Arg::with_name("IS_JSON")
             .long("--is_json")
             .help("Cli prints output in json format."))
@@ -519,6 +791,36 @@ async fn main_internal() -> Result <(), String> {
         .subcommand(SubCommand::with_name("reset")
             .about("Clear the aliases map."));
 
+    let network_name_arg = Arg::with_name("NAME")
+        .required(true)
+        .takes_value(true)
+        .help("Network profile name.");
+    let config_network_cmd = SubCommand::with_name("network")
+        .about("Commands to work with network profiles.")
+        .subcommand(SubCommand::with_name("add")
+            .about("Add network profile or replace the existing one.")
+            .arg(network_name_arg.clone())
+            .arg(Arg::with_name("URL")
+                .long("--url")
+                .required(true)
+                .takes_value(true)
+                .help("Url of the network."))
+            .arg(Arg::with_name("ENDPOINTS")
+                .long("--endpoints")
+                .takes_value(true)
+                .help("List of endpoints (comma separated). If not specified, endpoints are taken from the endpoints map for the url."))
+            .arg(Arg::with_name("CONFIG_ADDRESS")
+                .long("--config_address")
+                .takes_value(true)
+                .help("Address of the config contract in this network.")))
+        .subcommand(SubCommand::with_name("remove")
+            .about("Remove network profile.")
+            .arg(network_name_arg.clone()))
+        .subcommand(SubCommand::with_name("print")
+            .about("Print network profiles."))
+        .subcommand(SubCommand::with_name("reset")
+            .about("Remove all network profiles."));
+
     let url_arg = Arg::with_name("URL")
         .required(true)
         .takes_value(true)
@@ -596,7 +898,7 @@ async fn main_internal() -> Result <(), String> {
             .help("Network message processing timeout in ms."))
         .arg(Arg::with_name("LIST")
             .long("--list")
-            .conflicts_with_all(&["OUT_OF_SYNC", "NO_ANSWER","DEBUG_FAIL", "ASYNC_CALL", "LOCAL_RUN", "BALANCE_IN_TONS", "LIFETIME", "DEPOOL_FEE", "PUBKEY", "URL", "ABI", "KEYS", "ADDR", "RETRIES", "TIMEOUT", "WC", "WALLET"])
+            .conflicts_with_all(&["OUT_OF_SYNC", "WAIT_BLOCKS", "INCLUDE_TIMESTAMP", "CANONICAL_JSON", "UNLIMITED_BALANCE", "ECHO_PARAMS", "TOKEN_DECIMALS", "DEBUG_BC_CONFIG", "FEES_PERCENTAGE", "WAIT_LEVEL", "DUMP_ENCODE_PARAMS", "EMULATE_SRC", "CHECK_FROZEN", "STRUCTURED_TRACE", "STRICT_OUTPUT", "TABLE_OUTPUT", "CHECK_STORAGE_DUE", "UTC_TIME", "ADDRESS_FORMAT", "CONFIG_ADDRESS", "DECODE_LAYOUT", "POLL_INTERVAL_MS", "INCLUDE_BALANCE", "FEES_ON_ABORT", "RELAXED_JSON", "METRICS_FILE", "REPLAY_PROTECTION", "FEE_CONFIG", "MAX_MESSAGE_SIZE", "TREE_DEPTH", "FASTEST_ENDPOINT", "CHECK_INT_RANGE", "INCLUDE_BOC", "RESULT_FILE", "DUMMY_ACCOUNT", "NO_ANSWER","DEBUG_FAIL", "ASYNC_CALL", "LOCAL_RUN", "BALANCE_IN_TONS", "LIFETIME", "DEPOOL_FEE", "PUBKEY", "URL", "ABI", "KEYS", "ADDR", "RETRIES", "TIMEOUT", "WC", "WALLET"])
             .help("Prints all config parameters."))
         .arg(Arg::with_name("DEPOOL_FEE")
             .long("--depool_fee")
@@ -630,6 +932,142 @@ async fn main_internal() -> Result <(), String> {
             .long("--out_of_sync")
             .takes_value(true)
             .help("Network connection \"out_of_sync_threshold\" parameter in seconds. Mind that it cant exceed half of the \"lifetime\" parameter."))
+        .arg(Arg::with_name("WAIT_BLOCKS")
+            .long("--wait_blocks")
+            .takes_value(true)
+            .help("Number of masterchain blocks to wait for after the transaction is found before call or deploy is reported as succeeded. Default value is 0."))
+        .arg(Arg::with_name("DUMMY_ACCOUNT")
+            .long("--dummy_account")
+            .takes_value(true)
+            .help("Use a dummy account with unlimited balance for local run before call and deploy commands if the account does not exist yet."))
+        .arg(Arg::with_name("RESULT_FILE")
+            .long("--result_file")
+            .takes_value(true)
+            .help("Path to the file where results of call and run commands are saved instead of printing them to stdout."))
+        .arg(Arg::with_name("INCLUDE_BOC")
+            .long("--include_boc")
+            .takes_value(true)
+            .help("Include base64 bocs of the transaction out messages in the call result (\"out_messages_boc\" field)."))
+        .arg(Arg::with_name("CHECK_INT_RANGE")
+            .long("--check_int_range")
+            .takes_value(true)
+            .help("Check that integer arguments of the alternative syntax commands fit the width declared in the ABI (negative values for unsigned integers are rejected too)."))
+        .arg(Arg::with_name("FASTEST_ENDPOINT")
+            .long("--fastest_endpoint")
+            .takes_value(true)
+            .help("Ping configured endpoints before connecting and use the fastest-responding one first. If pinging fails, endpoints are used in the configured order."))
+        .arg(Arg::with_name("TREE_DEPTH")
+            .long("--tree_depth")
+            .takes_value(true)
+            .help("Depth of the tree of transactions triggered by call which is fetched and decoded after call (\"transaction_tree\" field of the result). Default value is 0 (tree is not fetched)."))
+        .arg(Arg::with_name("MAX_MESSAGE_SIZE")
+            .long("--max_message_size")
+            .takes_value(true)
+            .help("Maximum size of the encoded message boc in bytes which is checked before sending the message, e.g. 262144 (network limit). Default value is 0 (the message size is not checked)."))
+        .arg(Arg::with_name("FEE_CONFIG")
+            .long("--fee_config")
+            .takes_value(true)
+            .help("JSON object with basechain gas and forward fee prices (\"gas_price\", \"flat_gas_price\", \"lump_price\", \"bit_price\", \"cell_price\") used to build synthetic blockchain config for local emulation and fee estimation. Prices which are not specified are taken from mainnet defaults."))
+        .arg(Arg::with_name("REPLAY_PROTECTION")
+            .long("--replay_protection")
+            .takes_value(true)
+            .help("Replay protection scheme of called contracts. Possible values: \"None\" and \"Seqno\". If set to \"Seqno\", \"seqno\" argument of the call is compared with \"seqno\" field of the account data and a warning is printed if it is stale."))
+        .arg(Arg::with_name("METRICS_FILE")
+            .long("--metrics_file")
+            .takes_value(true)
+            .help("Path to the file where metrics of processed calls (calls_total, calls_failed, fee_nanotons_total, wait_seconds) are written in Prometheus text format after each command."))
+        .arg(Arg::with_name("RELAXED_JSON")
+            .long("--relaxed_json")
+            .takes_value(true)
+            .help("Accept relaxed JSON (JSON5-like: unquoted keys, single-quoted strings, trailing commas and comments) in json arguments of alternative syntax commands. It is normalized to strict JSON before encoding."))
+        .arg(Arg::with_name("FEES_ON_ABORT")
+            .long("--fees_on_abort")
+            .takes_value(true)
+            .help("If set to true, fee estimation of a call returns fees along with the abort reason when local run of the call fails."))
+        .arg(Arg::with_name("INCLUDE_BALANCE")
+            .long("--include_balance")
+            .takes_value(true)
+            .help("Include balance of the called account before and after the call in the call result (\"balance\" field). Requires additional queries."))
+        .arg(Arg::with_name("POLL_INTERVAL_MS")
+            .long("--poll_interval_ms")
+            .takes_value(true)
+            .help("Interval in ms between checks whether the transaction of the sent message has appeared. If 0 (default), waiting is fully done by the SDK."))
+        .arg(Arg::with_name("DECODE_LAYOUT")
+            .long("--decode_layout")
+            .takes_value(true)
+            .help("If set to true, decoded call parameters of `decode call` and `send` commands are printed as a tree annotating each field with its ABI type and cell layout."))
+        .arg(Arg::with_name("CONFIG_ADDRESS")
+            .long("--config_address")
+            .takes_value(true)
+            .help("Address of the config contract which is used to load blockchain config from the network. Default is -1:5555555555555555555555555555555555555555555555555555555555555555."))
+        .arg(Arg::with_name("ADDRESS_FORMAT")
+            .long("--address_format")
+            .takes_value(true)
+            .help("Format of the address fields in the decoded output of call and run commands: Raw (0:...), Base64 (url-safe, bounceable) or None (leave as decoded, default)."))
+        .arg(Arg::with_name("UTC_TIME")
+            .long("--utc_time")
+            .takes_value(true)
+            .help("If set to true, times (e.g. message expiration time) are printed in UTC instead of the local time zone."))
+        .arg(Arg::with_name("CHECK_STORAGE_DUE")
+            .long("--check_storage_due")
+            .takes_value(true)
+            .help("If set to true, storage due of the called account is printed before the call and a warning is printed if the account owes storage fees."))
+        .arg(Arg::with_name("TABLE_OUTPUT")
+            .long("--table_output")
+            .takes_value(true)
+            .help("If set to true, results which are arrays of structures are printed as tables (columns are taken from the first element). Other results and json mode are not affected."))
+        .arg(Arg::with_name("STRICT_OUTPUT")
+            .long("--strict_output")
+            .takes_value(true)
+            .help("If set to true, fields of the decoded call output are checked against the outputs of the function declared in the ABI and the command fails on mismatch (which usually means that the ABI doesn't match the contract)."))
+        .arg(Arg::with_name("STRUCTURED_TRACE")
+            .long("--structured_trace")
+            .takes_value(true)
+            .help("If set to true, debug on fail of call command additionally saves the trace in json format (steps with gas and stack) to `call_<address>_<method>.trace.json`. Trace is kept in memory during the execution."))
+        .arg(Arg::with_name("CHECK_FROZEN")
+            .long("--check_frozen")
+            .takes_value(true)
+            .help("If set to true, state of the called account is queried before sending the message and the call is aborted if the account is frozen."))
+        .arg(Arg::with_name("EMULATE_SRC")
+            .long("--emulate_src")
+            .takes_value(true)
+            .help("External source address (in form `:<hex>`) which is set to the external inbound message for local emulation (`local_run` config option and fee command). By default the message has no source address. Empty string resets it."))
+        .arg(Arg::with_name("DUMP_ENCODE_PARAMS")
+            .long("--dump_encode_params")
+            .takes_value(true)
+            .help("If set to true, call command prints parameters of the message encoding (address, header, signer, function and its input) as json before sending. Secret keys are redacted."))
+        .arg(Arg::with_name("WAIT_LEVEL")
+            .long("--wait_level")
+            .takes_value(true)
+            .help("Level of the message processing which call command waits for: Accepted (return message id and shard block as soon as the message is sent) or Processed (wait for the resulting transaction, default). Is ignored if async_call is set."))
+        .arg(Arg::with_name("FEES_PERCENTAGE")
+            .long("--fees_percentage")
+            .takes_value(true)
+            .help("If set to true, fee command additionally prints each fee as a percentage of the value attached to the call (\"value\" argument of the function). Is skipped if the value is not specified."))
+        .arg(Arg::with_name("DEBUG_BC_CONFIG")
+            .long("--debug_bc_config")
+            .takes_value(true)
+            .help("Path to the blockchain config which is used to debug failed calls (debug_fail option) instead of the config fetched from the network: boc of the config contract account or json file with config params. Empty value resets it."))
+        .arg(Arg::with_name("TOKEN_DECIMALS")
+            .long("--token_decimals")
+            .takes_value(true)
+            .help("Number of decimals of the token, which is used to convert amounts with `T` suffix of the function arguments (default value is 9, i.e. nanotons). Account balances are always shown in native tokens with 9 decimals."))
+        .arg(Arg::with_name("ECHO_PARAMS")
+            .long("--echo_params")
+            .takes_value(true)
+            .help("Print normalized arguments of the call along with its result (under \"params\" key in json mode), so the log shows exactly what was sent. Secret fields are redacted."))
+        .arg(Arg::with_name("UNLIMITED_BALANCE")
+            .long("--unlimited_balance")
+            .takes_value(true)
+            .help("Use unlimited account balance for preliminary local run (`local_run` config option), so that the logic of the method can be checked regardless of the account balance. Is ignored if `--emulate_balance` is specified."))
+        .arg(Arg::with_name("CANONICAL_JSON")
+            .long("--canonical_json")
+            .takes_value(true)
+            .help("Print results of calls and runs as canonical json (compact, with lexicographically sorted keys), so that the same result always produces the same bytes. Takes precedence over `table_output`."))
+        .arg(Arg::with_name("INCLUDE_TIMESTAMP")
+            .long("--include_timestamp")
+            .takes_value(true)
+            .help("Include UTC time (ISO 8601) of the call completion in json results of the call commands (\"timestamp\" field)."))
         .arg(Arg::with_name("IS_JSON")
             .long("--is_json")
             .takes_value(true)
@@ -644,6 +1082,7 @@ async fn main_internal() -> Result <(), String> {
             .help("Project secret or JWT in Evercloud (dashboard.evercloud.dev)."))
         .subcommand(config_clear_cmd)
         .subcommand(config_endpoint_cmd)
+        .subcommand(config_network_cmd)
         .subcommand(alias_cmd);
 
     let account_cmd = SubCommand::with_name("account")
@@ -681,6 +1120,14 @@ async fn main_internal() -> Result <(), String> {
             .takes_value(true)
             .help("Timeout in seconds (default value is 30)."));
 
+    let account_watch_cmd = SubCommand::with_name("account-watch")
+        .setting(AppSettings::AllowLeadingHyphen)
+        .about("Subscribes to inbound messages of the account and prints them decoded with the ABI.")
+        .version(version_string)
+        .author(author)
+        .arg(address_arg.clone())
+        .arg(abi_arg.clone());
+
     let query_raw = SubCommand::with_name("query-raw")
         .about("Executes a raw GraphQL query.")
         .version(version_string)
@@ -722,7 +1169,48 @@ async fn main_internal() -> Result <(), String> {
         .subcommand(deploy_cmd.clone()
             .about("Executes deploy locally, calculates fees and prints table of fees in nanotons."))
         .subcommand(call_cmd.clone()
-            .about("Executes call locally, calculates fees and prints table of all fees in nanotons."));
+            .about("Executes call locally, calculates fees and prints table of all fees in nanotons."))
+        .subcommand(SubCommand::with_name("forward")
+            .about("Estimates forward fee of the message in nanotons using the blockchain config.")
+            .version(version_string)
+            .author(author)
+            .arg(Arg::with_name("MESSAGE")
+                .required(true)
+                .takes_value(true)
+                .help("Message boc encoded in base64 or path to the file with message boc."))
+            .arg(Arg::with_name("SRC_WC")
+                .long("--src_wc")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .help("Workchain of the message source (default value is 0)."))
+            .arg(Arg::with_name("DST_WC")
+                .long("--dst_wc")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .help("Workchain of the message destination (default value is 0)."))
+            .arg(Arg::with_name("CONFIG_BOC")
+                .long("--saved_config")
+                .takes_value(true)
+                .help("Path to the file with saved config contract state. If not specified, config is fetched from the network.")))
+        .subcommand(SubCommand::with_name("message")
+            .about("Executes the message on the saved account state with the local executor without connecting to the network and prints table of fees in nanotons.")
+            .version(version_string)
+            .author(author)
+            .arg(Arg::with_name("MESSAGE")
+                .required(true)
+                .takes_value(true)
+                .help("Message boc encoded in base64 or path to the file with message boc."))
+            .arg(Arg::with_name("ACCOUNT_BOC")
+                .long("--account")
+                .required(true)
+                .takes_value(true)
+                .help("Path to the file with saved account state."))
+            .arg(Arg::with_name("CONFIG_BOC")
+                .long("--saved_config")
+                .takes_value(true)
+                .help("Path to the file with saved config contract state or json file with config params. If not specified, config is fetched from the network."))
+            .arg(now_arg.clone())
+            .arg(emulate_balance_arg.clone()));
 
     let proposal_cmd = SubCommand::with_name("proposal")
         .help("Proposal control commands.")
@@ -792,7 +1280,40 @@ async fn main_internal() -> Result <(), String> {
             .help("path to config-master files"))
         .arg(Arg::with_name("NEW_PARAM_FILE")
             .takes_value(true)
-            .help("New config param value"));
+            .help("New config param value"))
+        .arg(Arg::with_name("PARAM_INDEX")
+            .long("--param_index")
+            .takes_value(true)
+            .help("Number of the config param. If specified, new param file must contain base64 encoded boc of the param cell instead of json."))
+        .arg(Arg::with_name("PREVIEW")
+            .long("--preview")
+            .help("Deserializes the serialized param back and prints it as json along with the message."));
+
+    let estimate_update_config_cmd = SubCommand::with_name("estimate_update_config")
+        .about("Estimates fees of the sequence of update config messages by executing them locally on the config account.")
+        .arg(abi_arg.clone())
+        .arg(Arg::with_name("SEQNO")
+            .takes_value(true)
+            .help("Current seqno from config contract. It is incremented for every next message."))
+        .arg(Arg::with_name("CONFIG_MASTER_KEY_FILE")
+            .takes_value(true)
+            .help("path to config-master files"))
+        .arg(Arg::with_name("NEW_PARAM_FILES")
+            .required(true)
+            .takes_value(true)
+            .multiple(true)
+            .help("New config param values in the order of submitting."));
+
+    let verify_update_config_cmd = SubCommand::with_name("verify_update_config")
+        .about("Verifies signature of the message with update of config params generated by update_config command.")
+        .arg(Arg::with_name("MESSAGE")
+            .required(true)
+            .takes_value(true)
+            .help("Message in hex format as printed by update_config command."))
+        .arg(Arg::with_name("PUBKEY")
+            .required(true)
+            .takes_value(true)
+            .help("Public key of the config account in hex format."));
 
     let bcconfig_cmd = SubCommand::with_name("dump")
         .about("Commands to dump network entities.")
@@ -893,6 +1414,11 @@ async fn main_internal() -> Result <(), String> {
             .short("-u")
             .long("--url")
             .takes_value(true))
+        .arg(Arg::with_name("NETWORK_PROFILE")
+            .help("Name of the network profile from the config (see `config network` command) to connect.")
+            .long("--network")
+            .takes_value(true)
+            .conflicts_with("NETWORK"))
         .arg(Arg::with_name("CONFIG")
             .help("Path to the tonos-cli configuration file.")
             .short("-c")
@@ -906,13 +1432,18 @@ async fn main_internal() -> Result <(), String> {
         .subcommand(genphrase_cmd)
         .subcommand(genpubkey_cmd)
         .subcommand(getkeypair_cmd)
+        .subcommand(checkkeys_cmd)
         .subcommand(genaddr_cmd)
         .subcommand(deploy_cmd
             .arg(alias_arg_long.clone()))
         .subcommand(deploy_message_cmd)
         .subcommand(call_cmd)
+        .subcommand(compare_cmd)
         .subcommand(send_cmd)
+        .subcommand(send_queue_cmd)
+        .subcommand(cancel_message_cmd)
         .subcommand(message_cmd)
+        .subcommand(refresh_message_cmd)
         .subcommand(body_cmd)
         .subcommand(sign_cmd)
         .subcommand(run_cmd)
@@ -920,6 +1451,7 @@ async fn main_internal() -> Result <(), String> {
         .subcommand(config_cmd)
         .subcommand(account_cmd)
         .subcommand(account_wait_cmd)
+        .subcommand(account_watch_cmd)
         .subcommand(query_raw)
         .subcommand(fee_cmd)
         .subcommand(proposal_cmd)
@@ -939,6 +1471,8 @@ async fn main_internal() -> Result <(), String> {
         .subcommand(deployx_cmd)
         .subcommand(runx_cmd)
         .subcommand(update_config_param_cmd)
+        .subcommand(estimate_update_config_cmd)
+        .subcommand(verify_update_config_cmd)
         .setting(AppSettings::SubcommandRequired);
 #[cfg(feature = "sold")]
     let matches = matches.subcommand(create_compile_command());
@@ -955,7 +1489,9 @@ async fn main_internal() -> Result <(), String> {
 
     let is_json = matches.is_present("JSON");
 
-    command_parser(&matches, is_json).await
+    let result = command_parser(&matches, is_json).await;
+    let flushed = metrics::flush();
+    result.and(flushed)
         .map_err(|e| {
             if e.is_empty() {
                 e
@@ -985,6 +1521,12 @@ async fn command_parser(matches: &ArgMatches<'_>, is_json: bool) -> Result <(),
     }
 
     full_config.config.is_json |= is_json;
+    if let Some(metrics_file) = &full_config.config.metrics_file {
+        metrics::enable(Some(metrics_file.clone()));
+    }
+    if let Some(name) = matches.value_of("NETWORK_PROFILE") {
+        full_config.select_network(name)?;
+    }
     let config = &mut full_config.config;
 
     if let Some(url) = matches.value_of("NETWORK") {
@@ -1006,6 +1548,9 @@ async fn command_parser(matches: &ArgMatches<'_>, is_json: bool) -> Result <(),
     if let Some(m) = matches.subcommand_matches("call") {
         return call_command(m, config, CallType::Call).await;
     }
+    if let Some(m) = matches.subcommand_matches("compare") {
+        return call_command(m, config, CallType::Compare).await;
+    }
     if let Some(m) = matches.subcommand_matches("run") {
         return run_command(m, &full_config, false).await;
     }
@@ -1021,9 +1566,18 @@ async fn command_parser(matches: &ArgMatches<'_>, is_json: bool) -> Result <(),
     if let Some(m) = matches.subcommand_matches("message") {
         return call_command(m, config, CallType::Msg).await;
     }
+    if let Some(m) = matches.subcommand_matches("refresh-message") {
+        return refresh_message_command(m, config).await;
+    }
     if let Some(m) = matches.subcommand_matches("send") {
         return send_command(m, config).await;
     }
+    if let Some(m) = matches.subcommand_matches("send-queue") {
+        return send_queue_command(m, config).await;
+    }
+    if let Some(m) = matches.subcommand_matches("cancel-message") {
+        return cancel_message_command(m, config);
+    }
     if let Some(m) = matches.subcommand_matches("deploy") {
         return deploy_command(m, &mut full_config, DeployType::Full).await;
     }
@@ -1036,6 +1590,9 @@ async fn command_parser(matches: &ArgMatches<'_>, is_json: bool) -> Result <(),
     if let Some(m) = matches.subcommand_matches("getkeypair") {
         return getkeypair_command(m, config);
     }
+    if let Some(m) = matches.subcommand_matches("checkkeys") {
+        return check_keypair(m.value_of("KEY_FILE").unwrap(), config.is_json);
+    }
     if let Some(m) = matches.subcommand_matches("account") {
         return account_command(m, config).await;
     }
@@ -1049,6 +1606,12 @@ async fn command_parser(matches: &ArgMatches<'_>, is_json: bool) -> Result <(),
         if let Some(m) = m.subcommand_matches("call") {
             return call_command(m, config, CallType::Fee).await;
         }
+        if let Some(m) = m.subcommand_matches("forward") {
+            return forward_fee_command(m, config).await;
+        }
+        if let Some(m) = m.subcommand_matches("message") {
+            return message_fee_command(m, config).await;
+        }
     }
     if let Some(m) = matches.subcommand_matches("genphrase") {
         return genphrase_command(m, config);
@@ -1079,6 +1642,12 @@ async fn command_parser(matches: &ArgMatches<'_>, is_json: bool) -> Result <(),
     if let Some(m) = matches.subcommand_matches("update_config") {
         return update_config_command(m, config).await;
     }
+    if let Some(m) = matches.subcommand_matches("estimate_update_config") {
+        return estimate_update_config_command(m, config).await;
+    }
+    if let Some(m) = matches.subcommand_matches("verify_update_config") {
+        return verify_update_config_command(m, config);
+    }
     if let Some(matches) = matches.subcommand_matches("dump") {
         if let Some(m) = matches.subcommand_matches("config") {
             return dump_bc_config_command(m, config).await;
@@ -1090,6 +1659,9 @@ async fn command_parser(matches: &ArgMatches<'_>, is_json: bool) -> Result <(),
     if let Some(m) = matches.subcommand_matches("account-wait") {
         return account_wait_command(m, config).await;
     }
+    if let Some(m) = matches.subcommand_matches("account-watch") {
+        return account_watch_command(m, config).await;
+    }
     if let Some(m) = matches.subcommand_matches("query-raw") {
         return query_raw_command(m, config).await;
     }
@@ -1157,10 +1729,11 @@ fn genpubkey_command(matches: &ArgMatches, config: &Config) -> Result<(), String
 fn getkeypair_command(matches: &ArgMatches, config: &Config) -> Result<(), String> {
     let key_file = matches.value_of("KEY_FILE");
     let phrase = matches.value_of("PHRASE");
+    let encrypt = matches.is_present("ENCRYPT");
     if !config.is_json {
         print_args!(key_file, phrase);
     }
-    generate_keypair(key_file, phrase, config)
+    generate_keypair(key_file, phrase, encrypt, config)
 }
 
 async fn send_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
@@ -1174,6 +1747,51 @@ async fn send_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), S
     call_contract_with_msg(config, message.unwrap().to_owned(), &abi.unwrap()).await
 }
 
+async fn send_queue_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
+    let queue = matches.value_of("QUEUE");
+    let abi = Some(abi_from_matches_or_config(matches, &config)?);
+
+    if !config.is_json {
+        print_args!(queue, abi);
+    }
+
+    send_queue(config, queue.unwrap(), &abi.unwrap()).await
+}
+
+fn cancel_message_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
+    let queue = matches.value_of("QUEUE");
+    let message_id = matches.value_of("MESSAGE_ID");
+
+    if !config.is_json {
+        print_args!(queue, message_id);
+    }
+
+    cancel_queued_message(config, queue.unwrap(), message_id.unwrap())
+}
+
+async fn refresh_message_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
+    let message = matches.value_of("MESSAGE");
+    let lifetime = matches.value_of("LIFETIME");
+    let raw = matches.is_present("RAW");
+    let output = matches.value_of("OUTPUT");
+    let abi = Some(abi_from_matches_or_config(matches, &config)?);
+    let keys = matches.value_of("KEYS")
+        .or(matches.value_of("SIGN"))
+        .map(|s| s.to_string())
+        .or(config.keys_path.clone());
+    if !config.is_json {
+        print_args!(message, abi, keys, lifetime, output);
+    }
+    let lifetime = lifetime.map(|val| {
+            u32::from_str_radix(val, 10)
+                .map_err(|e| format!("Failed to parse lifetime: {e}"))
+        })
+        .transpose()?
+        .unwrap_or(DEF_MSG_LIFETIME);
+    let edits = matches.value_of("EDIT_PARAMS").map(load_params).transpose()?;
+    refresh_message(config, message.unwrap(), &abi.unwrap(), keys, lifetime, raw, output, edits.as_deref()).await
+}
+
 async fn body_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
     let method = matches.value_of("METHOD");
     let params = matches.value_of("PARAMS");
@@ -1247,6 +1865,78 @@ fn sign_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String>
     Ok(())
 }
 
+// Call options which are parsed in the same way by `call` and `callx` commands.
+struct ParsedCallOptions {
+    header: Option<FunctionHeader>,
+    expire_at: Option<u32>,
+    now: Option<u32>,
+    init_state: Option<InitState>,
+    balance: Option<u64>,
+    expected: Option<String>,
+    exact: bool,
+}
+
+fn parse_call_options(matches: &ArgMatches<'_>) -> Result<ParsedCallOptions, String> {
+    let header = matches.value_of("HEADER")
+        .map(|header| load_params(header).and_then(|header| parse_header_override(&header)))
+        .transpose()?;
+    let expire_at = matches.value_of("EXPIRE_AT").map(parse_expire_at).transpose()?;
+    let header = match expire_at {
+        Some(expire_at) => Some(apply_expire_at(header, expire_at)?),
+        None => header,
+    };
+    let now = matches.value_of("NOW")
+        .map(|now| u32::from_str_radix(now, 10)
+            .map_err(|e| format!("Failed to parse now: {e}")))
+        .transpose()?;
+    let init_state = matches.value_of("INIT_TVC")
+        .map(|tvc| InitState::new(tvc, matches.value_of("INIT_PUBKEY")))
+        .transpose()?;
+    let balance = matches.value_of("EMULATE_BALANCE")
+        .map(|balance| u64::from_str_radix(balance, 10)
+            .map_err(|e| format!("Failed to parse emulate_balance: {e}")))
+        .transpose()?;
+    let expected = matches.value_of("EXPECT").map(load_params).transpose()?;
+    let exact = matches.is_present("EXPECT_EXACT");
+    Ok(ParsedCallOptions { header, expire_at, now, init_state, balance, expected, exact })
+}
+
+// Selects the keyfile by label and checks the code hash of the called contract if these options are set.
+async fn select_call_keys(
+    matches: &ArgMatches<'_>,
+    config: &Config,
+    keys: Option<String>,
+    address: &str,
+) -> Result<Option<String>, String> {
+    let keys = select_keyfile(keys, matches.value_of("KEY_LABEL"), address)?;
+    if let Some(code_hash) = matches.value_of("CODE_HASH") {
+        check_code_hash(config, address, code_hash).await?;
+    }
+    Ok(keys)
+}
+
+// Calls the contract and checks the result if it is expected (fee estimation is never checked).
+async fn run_call(
+    config: &Config,
+    address: &str,
+    abi: &str,
+    method: &str,
+    params: &str,
+    keys: Option<String>,
+    is_fee: bool,
+    call_options: ParsedCallOptions,
+) -> Result<(), String> {
+    let ParsedCallOptions { header, now, init_state, balance, expected, exact, .. } = call_options;
+    let options = CallOptions { header, now, init_state, balance, ..Default::default() };
+    match expected {
+        Some(expected) if !is_fee => {
+            let result = call_contract_with_result(config, address, abi, method, params, keys, false, options).await?;
+            check_call_result(result, &expected, exact, config)
+        },
+        _ => call_contract(config, address, abi, method, params, keys, is_fee, options).await,
+    }
+}
+
 async fn call_command(matches: &ArgMatches<'_>, config: &Config, call: CallType) -> Result<(), String> {
     let address = matches.value_of("ADDRESS");
     let method = matches.value_of("METHOD");
@@ -1256,6 +1946,8 @@ async fn call_command(matches: &ArgMatches<'_>, config: &Config, call: CallType)
     let output = matches.value_of("OUTPUT");
 
     let abi = Some(abi_from_matches_or_config(matches, &config)?);
+    let method = resolve_method(abi.as_ref().unwrap(), method.unwrap(), config).await?;
+    let method = Some(method.as_str());
 
     let keys = matches.value_of("KEYS")
         .or(matches.value_of("SIGN"))
@@ -1263,25 +1955,50 @@ async fn call_command(matches: &ArgMatches<'_>, config: &Config, call: CallType)
         .or(config.keys_path.clone());
 
     let params = Some(load_params(params.unwrap())?);
+    let params = match matches.value_of("VALUE") {
+        Some(value) => Some(attach_value(&params.unwrap(), value, abi.as_ref().unwrap(), method.unwrap(), config).await?),
+        None => params,
+    };
+    let params = match matches.value_of("COMMENT") {
+        Some(comment) => Some(attach_comment(&params.unwrap(), comment, abi.as_ref().unwrap(), method.unwrap(), config).await?),
+        None => params,
+    };
+    let call_options = parse_call_options(matches)?;
+    let expire_at = call_options.expire_at;
     if !config.is_json {
         print_args!(address, method, params, abi, keys, lifetime, output);
     }
     let address = load_ton_address(address.unwrap(), &config)?;
+    let keys = select_call_keys(matches, config, keys, &address).await?;
 
     match call {
         CallType::Call | CallType::Fee => {
             let is_fee = if let CallType::Fee = call { true } else { false };
-            call_contract(
+            run_call(
                 config,
                 address.as_str(),
-                &abi.unwrap(),
+                abi.as_ref().unwrap(),
                 method.unwrap(),
                 &params.unwrap(),
                 keys,
                 is_fee,
+                call_options,
+            ).await
+        },
+        CallType::Compare => {
+            compare_local_and_onchain(
+                config,
+                address.as_str(),
+                &abi.unwrap(),
+                method.unwrap(),
+                &params.unwrap(),
+                keys,
             ).await
         },
         CallType::Msg => {
+            if lifetime.is_some() && expire_at.is_some() {
+                return Err("lifetime and expire_at can't be specified together".to_string());
+            }
             let lifetime = lifetime.map(|val| {
                     u32::from_str_radix(val, 10)
                         .map_err(|e| format!("Failed to parse lifetime: {e}"))
@@ -1303,6 +2020,8 @@ async fn call_command(matches: &ArgMatches<'_>, config: &Config, call: CallType)
                 raw,
                 output,
                 timestamp,
+                matches.value_of("QUEUE"),
+                expire_at,
             ).await
         },
     }
@@ -1313,6 +2032,8 @@ async fn callx_command(matches: &ArgMatches<'_>, full_config: &FullConfig) -> Re
     let method = Some(matches.value_of("METHOD").or(config.method.as_deref())
         .ok_or("Method is not defined. Supply it in the config file or command line.")?);
     let (address, abi, keys) = contract_data_from_matches_or_config_alias(matches, full_config)?;
+    let method = resolve_method(abi.as_ref().unwrap(), method.unwrap(), config).await?;
+    let method = Some(method.as_str());
     let params = unpack_alternative_params(
         matches,
         abi.as_ref().unwrap(),
@@ -1320,21 +2041,28 @@ async fn callx_command(matches: &ArgMatches<'_>, full_config: &FullConfig) -> Re
         config
     ).await?;
     let params = Some(load_params(params.unwrap().as_ref())?);
+    let params = match matches.value_of("BATCH") {
+        Some(calls) => Some(build_batch_params(&load_params(calls)?, abi.as_ref().unwrap(), method.unwrap(), config).await?),
+        None => params,
+    };
+    let call_options = parse_call_options(matches)?;
 
     if !config.is_json {
         print_args!(address, method, params, abi, keys);
     }
 
     let address = load_ton_address(address.unwrap().as_str(), &config)?;
+    let keys = select_call_keys(matches, config, keys, &address).await?;
 
-    call_contract(
+    run_call(
         config,
         address.as_str(),
-        &abi.unwrap(),
+        abi.as_ref().unwrap(),
         &method.unwrap(),
         &params.unwrap(),
         keys,
         false,
+        call_options,
     ).await
 }
 
@@ -1434,6 +2162,22 @@ fn config_command(matches: &ArgMatches, mut full_config: FullConfig, is_json: bo
             }
             FullConfig::print_endpoints(full_config.path.as_str());
             return Ok(());
+        } else if let Some(network_matches) = matches.subcommand_matches("network") {
+            if let Some(network_matches) = network_matches.subcommand_matches("add") {
+                full_config.add_network(
+                    network_matches.value_of("NAME").unwrap(),
+                    network_matches.value_of("URL").unwrap(),
+                    network_matches.value_of("ENDPOINTS"),
+                    network_matches.value_of("CONFIG_ADDRESS").map(|s| s.to_string()),
+                )?
+            } else if let Some(network_matches) = network_matches.subcommand_matches("remove") {
+                full_config.remove_network(network_matches.value_of("NAME").unwrap())?
+            } else if network_matches.subcommand_matches("reset").is_some() {
+                full_config.networks = BTreeMap::new();
+                full_config.to_file(&full_config.path)?;
+            }
+            full_config.print_networks();
+            return Ok(());
         } else if let Some(alias_matches) = matches.subcommand_matches("alias") {
             if let Some(alias_matches) = alias_matches.subcommand_matches("add") {
                 full_config.add_alias(
@@ -1544,6 +2288,16 @@ async fn account_wait_command(matches: &ArgMatches<'_>, config: &Config) -> Resu
     wait_for_change(config, &address, timeout).await
 }
 
+async fn account_watch_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
+    let address = matches.value_of("ADDRESS").unwrap();
+    let address = load_ton_address(address, &config)?;
+    let abi = Some(abi_from_matches_or_config(matches, config)?);
+    if !config.is_json {
+        print_args!(address, abi);
+    }
+    watch_account(config, &address, &abi.unwrap()).await
+}
+
 async fn query_raw_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
     let collection = matches.value_of("COLLECTION").unwrap();
     let filter = matches.value_of("FILTER");
@@ -1553,6 +2307,66 @@ async fn query_raw_command(matches: &ArgMatches<'_>, config: &Config) -> Result<
     query_raw(config, collection, filter, limit, order, result).await
 }
 
+async fn forward_fee_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
+    let message = matches.value_of("MESSAGE");
+    let src_wc = matches.value_of("SRC_WC");
+    let dst_wc = matches.value_of("DST_WC");
+    let saved_config = matches.value_of("CONFIG_BOC");
+    if !config.is_json {
+        print_args!(message, src_wc, dst_wc, saved_config);
+    }
+    let parse_wc = |wc: Option<&str>| wc.map(|wc| i32::from_str_radix(wc, 10)
+            .map_err(|e| format!("failed to parse workchain id: {}", e)))
+        .transpose()
+        .map(|wc| wc.unwrap_or(0));
+    let src_wc = parse_wc(src_wc)?;
+    let dst_wc = parse_wc(dst_wc)?;
+    let message = message.unwrap();
+    let message = match std::fs::read(message) {
+        Ok(bytes) => base64::encode(&bytes),
+        Err(_) => message.to_owned(),
+    };
+    let bc_config = get_blockchain_config(config, saved_config).await?;
+    let fee = estimate_forward_fee(&bc_config, src_wc, dst_wc, &message)?;
+    if !config.is_json {
+        println!("Forward fee: {}", fee);
+    } else {
+        println!("{:#}", json!({ "fwd_fee": fee.to_string() }));
+    }
+    Ok(())
+}
+
+async fn message_fee_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
+    let message = matches.value_of("MESSAGE");
+    let account = matches.value_of("ACCOUNT_BOC");
+    let saved_config = matches.value_of("CONFIG_BOC");
+    let now = matches.value_of("NOW");
+    let balance = matches.value_of("EMULATE_BALANCE");
+    if !config.is_json {
+        print_args!(message, account, saved_config, now, balance);
+    }
+    let now = now
+        .map(|now| u32::from_str_radix(now, 10)
+            .map_err(|e| format!("Failed to parse now: {e}")))
+        .transpose()?;
+    let balance = balance
+        .map(|balance| u64::from_str_radix(balance, 10)
+            .map_err(|e| format!("Failed to parse emulate_balance: {e}")))
+        .transpose()?;
+    let message = message.unwrap();
+    let message = match std::fs::read(message) {
+        Ok(bytes) => base64::encode(&bytes),
+        Err(_) => message.to_owned(),
+    };
+    let account = account.unwrap();
+    let account = std::fs::read(account)
+        .map_err(|e| format!("failed to read account state from file {}: {}", account, e))?;
+    let bc_config = get_blockchain_config(config, saved_config).await?;
+    let (_, fees) = emulate_locally_offline(&base64::encode(&account), &message, bc_config, now, balance)?;
+    print_local_run_fees(&fees);
+    Ok(())
+}
+
 async fn storage_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
     let address = matches.value_of("ADDRESS");
     let period = matches.value_of("PERIOD");
@@ -1633,10 +2447,35 @@ async fn update_config_command(matches: &ArgMatches<'_>, config: &Config) -> Res
     let seqno = matches.value_of("SEQNO");
     let config_master = matches.value_of("CONFIG_MASTER_KEY_FILE");
     let new_param = matches.value_of("NEW_PARAM_FILE");
+    let param_index = matches.value_of("PARAM_INDEX");
+    if !config.is_json {
+        print_args!(seqno, config_master, new_param, param_index);
+    }
+    let param_index = param_index
+        .map(|index| index.parse::<u32>().map_err(|e| format!(r#"failed to parse "param_index": {}"#, e)))
+        .transpose()?;
+    gen_update_config_message(abi, seqno, config_master.unwrap(), new_param.unwrap(), param_index, matches.is_present("PREVIEW"), config.is_json).await
+}
+
+async fn estimate_update_config_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
+    let abi = matches.value_of("ABI");
+    let seqno = matches.value_of("SEQNO");
+    let config_master = matches.value_of("CONFIG_MASTER_KEY_FILE");
+    let new_params = matches.values_of("NEW_PARAM_FILES").unwrap().collect::<Vec<_>>();
+    if !config.is_json {
+        let new_params = Some(new_params.join(" "));
+        print_args!(seqno, config_master, new_params);
+    }
+    estimate_update_config(config, abi, seqno, config_master.unwrap(), new_params).await
+}
+
+fn verify_update_config_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
+    let message = matches.value_of("MESSAGE");
+    let pubkey = matches.value_of("PUBKEY");
     if !config.is_json {
-        print_args!(seqno, config_master, new_param);
+        print_args!(message, pubkey);
     }
-    gen_update_config_message(abi, seqno, config_master.unwrap(), new_param.unwrap(), config.is_json).await
+    verify_update_config_message(message.unwrap(), pubkey.unwrap(), config.is_json)
 }
 
 async fn dump_bc_config_command(matches: &ArgMatches<'_>, config: &Config) -> Result<(), String> {
@@ -11,12 +11,14 @@
  * limitations under the License.
  */
 
-use chrono::{Local, TimeZone};
+use chrono::{Local, TimeZone, Utc};
 use serde_json::json;
-use ton_client::abi::{Abi, CallSet, encode_message, FunctionHeader, ParamsOfEncodeMessage, Signer};
+use ton_client::abi::{Abi, CallSet, decode_message, encode_message, FunctionHeader, ParamsOfDecodeMessage,
+                      ParamsOfEncodeMessage, Signer};
 use crate::config::Config;
-use crate::helpers::{create_client_local, load_abi, load_ton_address, now, TonClient};
+use crate::helpers::{create_client_local, load_abi, load_ton_address, now, now_ms, TonClient};
 use crate::crypto::load_keypair;
+use ton_block::{Deserializable, Message};
 
 pub struct EncodedMessage {
     pub message_id: String,
@@ -83,20 +85,42 @@ pub fn prepare_message_params (
     })
 }
 
-pub fn print_encoded_message(msg: &EncodedMessage, is_json:bool) {
-    let expire = if msg.expire.is_some() {
-        let expire_at = Local.timestamp_opt(msg.expire.unwrap() as i64, 0).single().unwrap();
-        expire_at.to_rfc2822()
+// Formats expiration time in rfc2822 (in UTC or in the local time zone) and relative to `now`.
+fn format_expire(expire: u32, now: u32, utc: bool) -> (String, String) {
+    let expire_at = if utc {
+        Utc.timestamp_opt(expire as i64, 0).single().map(|time| time.to_rfc2822())
     } else {
-        "unknown".to_string()
+        Local.timestamp_opt(expire as i64, 0).single().map(|time| time.to_rfc2822())
     };
-    if !is_json {
+    let relative = if expire >= now {
+        format!("in {}s", expire - now)
+    } else {
+        format!("{}s ago", now - expire)
+    };
+    (expire_at.unwrap_or_else(|| expire.to_string()), relative)
+}
+
+pub fn print_encoded_message(msg: &EncodedMessage, config: &Config) {
+    let (expire, relative) = match msg.expire {
+        Some(expire) => {
+            let (expire, relative) = format_expire(expire, now().unwrap_or(0), config.utc_time);
+            (expire, Some(relative))
+        },
+        None => ("unknown".to_string(), None),
+    };
+    if !config.is_json {
         println!();
         println!("MessageId: {}", msg.message_id);
-        println!("Expire at: {}", expire);
+        match relative {
+            Some(relative) => println!("Expire at: {} ({})", expire, relative),
+            None => println!("Expire at: {}", expire),
+        }
     } else {
         println!("  \"MessageId\": \"{}\",", msg.message_id);
         println!("  \"Expire at\": \"{}\",", expire);
+        if let Some(relative) = relative {
+            println!("  \"Expire in\": \"{}\",", relative);
+        }
     }
 }
 
@@ -128,6 +152,11 @@ pub fn unpack_message(str_msg: &str) -> Result<(EncodedMessage, String), String>
     let str_msg = std::str::from_utf8(&bytes)
         .map_err(|e| format!("message is corrupted: {}", e))?;
 
+    unpack_json_message(str_msg)
+}
+
+/// Parses message packed by `pack_message` without `is_raw` flag and not encoded to hex.
+pub fn unpack_json_message(str_msg: &str) -> Result<(EncodedMessage, String), String> {
     let json_msg: serde_json::Value = serde_json::from_str(str_msg)
         .map_err(|e| format!("couldn't decode message: {}", e))?;
 
@@ -151,6 +180,27 @@ pub fn unpack_message(str_msg: &str) -> Result<(EncodedMessage, String), String>
     Ok((msg, method))
 }
 
+/// Appends the message to the queue file. Queue file contains one packed message per line.
+pub fn enqueue_message(msg: &EncodedMessage, method: &str, path: &str, is_json: bool) -> Result<(), String> {
+    use std::io::Write;
+    let mut line = pack_message(msg, method, false)?;
+    line.push(b'\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&line))
+        .map_err(|e| format!("failed to write message to queue file {}: {}", path, e))?;
+    if !is_json {
+        println!();
+        println!("MessageId: {}", msg.message_id);
+        println!("Message added to queue {}", path);
+    } else {
+        println!("{:#}", json!({ "MessageId": msg.message_id, "Queue": path }));
+    }
+    Ok(())
+}
+
 pub async fn generate_message(
     config: &Config,
     addr: &str,
@@ -162,6 +212,8 @@ pub async fn generate_message(
     is_raw: bool,
     output: Option<&str>,
     timestamp: Option<u64>,
+    queue: Option<&str>,
+    expire_at: Option<u32>,
 ) -> Result<(), String> {
     let ton = create_client_local()?;
 
@@ -170,7 +222,10 @@ pub async fn generate_message(
 
     let abi = load_abi(abi, config).await?;
 
-    let expire_at = lifetime + timestamp.clone().map(|millis| (millis / 1000) as u32).unwrap_or(now()?);
+    let expire_at = match expire_at {
+        Some(expire_at) => expire_at,
+        None => lifetime + timestamp.clone().map(|millis| (millis / 1000) as u32).unwrap_or(now()?),
+    };
     let header = FunctionHeader {
         expire: Some(expire_at),
         time: timestamp,
@@ -188,22 +243,135 @@ pub async fn generate_message(
         config.is_json,
     ).await?;
 
-    display_generated_message(&msg, method, is_raw, output, config.is_json)?;
+    if let Some(queue) = queue {
+        return enqueue_message(&msg, method, queue, config.is_json);
+    }
+    display_generated_message(&msg, method, is_raw, output, config)?;
 
     Ok(())
 }
 
+async fn decode_message_call(ton: TonClient, abi: Abi, message: &str) -> Result<(String, serde_json::Value), String> {
+    let decoded = decode_message(
+        ton,
+        ParamsOfDecodeMessage {
+            abi,
+            message: message.to_owned(),
+            ..Default::default()
+        },
+    ).await
+        .map_err(|e| format!("couldn't decode message: {}", e))?;
+    Ok((decoded.name, decoded.value.unwrap_or(json!({}))))
+}
+
+// Returns destination address of the message in base64 boc.
+fn message_destination(message: &str) -> Result<String, String> {
+    let message = Message::construct_from_base64(message)
+        .map_err(|e| format!("failed to construct message from base64 boc: {}", e))?;
+    message.dst()
+        .map(|address| address.to_string())
+        .ok_or("message has no destination address".to_string())
+}
+
+// Overrides arguments decoded from the message with the edited ones.
+fn edit_params(mut params: serde_json::Value, edits: &str) -> Result<serde_json::Value, String> {
+    let edits: serde_json::Value = serde_json::from_str(edits)
+        .map_err(|e| format!("edited arguments are not in json format: {}", e))?;
+    let edits = edits.as_object()
+        .ok_or("edited arguments must be a json object".to_string())?;
+    let params_map = params.as_object_mut()
+        .ok_or("decoded arguments are not a json object".to_string())?;
+    for (name, value) in edits {
+        if !params_map.contains_key(name) {
+            return Err(format!(r#"function has no "{}" argument"#, name));
+        }
+        params_map.insert(name.clone(), value.clone());
+    }
+    Ok(params)
+}
+
+// Compares decoded arguments with the expected ones. Edited integers can be specified as json numbers,
+// while they are decoded as strings.
+fn same_params(decoded: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    match (decoded, expected) {
+        (serde_json::Value::String(decoded), serde_json::Value::Number(expected)) => *decoded == expected.to_string(),
+        (serde_json::Value::Object(decoded), serde_json::Value::Object(expected)) => decoded.len() == expected.len()
+            && decoded.iter().all(|(name, value)| expected.get(name).map_or(false, |expected| same_params(value, expected))),
+        (serde_json::Value::Array(decoded), serde_json::Value::Array(expected)) => decoded.len() == expected.len()
+            && decoded.iter().zip(expected).all(|(decoded, expected)| same_params(decoded, expected)),
+        (decoded, expected) => decoded == expected,
+    }
+}
+
+/// Re-encodes message generated by `generate_message` with new time and expire, and signs it again.
+/// Function and its arguments are decoded from the original message with the ABI. Message can also
+/// be specified as base64 boc (e.g. captured from the network) and its arguments can be edited.
+pub async fn refresh_message(
+    config: &Config,
+    str_msg: &str,
+    abi: &str,
+    keys: Option<String>,
+    lifetime: u32,
+    is_raw: bool,
+    output: Option<&str>,
+    edits: Option<&str>,
+) -> Result<(), String> {
+    let ton = create_client_local()?;
+    let abi = load_abi(abi, config).await?;
+    let (message, address, expected) = match unpack_message(str_msg) {
+        Ok((msg, method)) => (msg.message, msg.address, Some(method)),
+        Err(_) => (str_msg.to_owned(), message_destination(str_msg)?, None),
+    };
+
+    let (method, params) = decode_message_call(ton.clone(), abi.clone(), &message).await?;
+    if let Some(expected) = expected {
+        if method != expected {
+            return Err(format!(r#"message is corrupted: it calls "{}" function, but "{}" is expected"#, method, expected));
+        }
+    }
+    let params = match edits {
+        Some(edits) => edit_params(params, edits)?,
+        None => params,
+    };
+    let str_params = serde_json::to_string(&params)
+        .map_err(|e| format!("failed to serialize message arguments: {}", e))?;
+
+    let header = FunctionHeader {
+        expire: Some(now()? + lifetime),
+        time: Some(now_ms()),
+        ..Default::default()
+    };
+    let new_msg = prepare_message(
+        ton.clone(),
+        &address,
+        abi.clone(),
+        &method,
+        &str_params,
+        Some(header),
+        keys,
+        config.is_json,
+    ).await?;
+
+    let (new_name, new_params) = decode_message_call(ton.clone(), abi, &new_msg.message).await?;
+    if new_name != method || !same_params(&new_params, &params) {
+        return Err("refreshed message body doesn't match the original message".to_string());
+    }
+
+    display_generated_message(&new_msg, &method, is_raw, output, config)
+}
+
 pub fn display_generated_message(
     msg: &EncodedMessage,
     method: &str,
     is_raw: bool,
     output: Option<&str>,
-    is_json: bool,
+    config: &Config,
 ) -> Result<(), String> {
+    let is_json = config.is_json;
     if is_json {
         println!("{{");
     }
-    print_encoded_message(msg, is_json);
+    print_encoded_message(msg, config);
 
     let msg_bytes = pack_message(msg, method, is_raw)?;
     if output.is_some() {
@@ -232,3 +400,32 @@ pub fn display_generated_message(
     Ok(())
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_params() {
+        let params = json!({ "dest": "0:01", "value": "1000", "bounce": true });
+        let edited = edit_params(params.clone(), r#"{"value":"2000","bounce":false}"#).unwrap();
+        assert_eq!(edited, json!({ "dest": "0:01", "value": "2000", "bounce": false }));
+        assert_eq!(edit_params(params.clone(), "{}").unwrap(), params);
+        assert!(edit_params(params.clone(), r#"{"amount":"2000"}"#).is_err());
+        assert!(edit_params(params.clone(), r#"["2000"]"#).is_err());
+        assert!(edit_params(params, "value=2000").is_err());
+
+        let decoded = json!({ "dest": "0:01", "value": "2000", "ids": ["1", "2"] });
+        assert!(same_params(&decoded, &json!({ "dest": "0:01", "value": 2000, "ids": [1, "2"] })));
+        assert!(!same_params(&decoded, &json!({ "dest": "0:01", "value": 3000, "ids": [1, 2] })));
+        assert!(!same_params(&decoded, &json!({ "dest": "0:01", "value": "2000" })));
+    }
+
+    #[test]
+    fn test_format_expire() {
+        let (expire, relative) = format_expire(1650000060, 1650000000, true);
+        assert_eq!(expire, "Fri, 15 Apr 2022 05:21:00 +0000");
+        assert_eq!(relative, "in 60s");
+        assert_eq!(format_expire(1650000000, 1650000060, true).1, "60s ago");
+    }
+}
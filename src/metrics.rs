@@ -0,0 +1,105 @@
+/*
+ * Copyright 2018-2021 TON DEV SOLUTIONS LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ */
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use lazy_static::lazy_static;
+use serde::Serialize;
+use ton_client::processing::ResultOfProcessMessage;
+
+// Counters are updated only after `enable` is called, so they cost one atomic load otherwise.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CALLS_TOTAL: AtomicU64 = AtomicU64::new(0);
+static CALLS_FAILED: AtomicU64 = AtomicU64::new(0);
+static FEE_NANOTONS_TOTAL: AtomicU64 = AtomicU64::new(0);
+static WAIT_MILLIS: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    static ref OUTPUT_FILE: Mutex<Option<String>> = Mutex::new(None);
+}
+
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    pub calls_total: u64,
+    pub calls_failed: u64,
+    pub fee_nanotons_total: u64,
+    pub wait_seconds: f64,
+}
+
+impl MetricsSnapshot {
+    /// Formats metrics in Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        format!(
+            "# TYPE tonoscli_calls_total counter\n\
+            tonoscli_calls_total {}\n\
+            # TYPE tonoscli_calls_failed counter\n\
+            tonoscli_calls_failed {}\n\
+            # TYPE tonoscli_fee_nanotons_total counter\n\
+            tonoscli_fee_nanotons_total {}\n\
+            # TYPE tonoscli_wait_seconds counter\n\
+            tonoscli_wait_seconds {}\n",
+            self.calls_total, self.calls_failed, self.fee_nanotons_total, self.wait_seconds,
+        )
+    }
+}
+
+/// Enables metrics collection. If output file is specified, metrics are written to it by `flush`.
+pub fn enable(output_file: Option<String>) {
+    *OUTPUT_FILE.lock().unwrap() = output_file;
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Records processed message. `fees` is None if processing failed.
+pub fn record_call(fees: Option<u64>, wait: Duration) {
+    if !is_enabled() {
+        return;
+    }
+    CALLS_TOTAL.fetch_add(1, Ordering::Relaxed);
+    match fees {
+        Some(fees) => { FEE_NANOTONS_TOTAL.fetch_add(fees, Ordering::Relaxed); },
+        None => { CALLS_FAILED.fetch_add(1, Ordering::Relaxed); },
+    }
+    WAIT_MILLIS.fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+}
+
+pub fn record_processing<E>(result: &Result<ResultOfProcessMessage, E>, start: Instant) {
+    if !is_enabled() {
+        return;
+    }
+    record_call(result.as_ref().ok().map(|res| res.fees.total_account_fees), start.elapsed());
+}
+
+pub fn snapshot() -> MetricsSnapshot {
+    MetricsSnapshot {
+        calls_total: CALLS_TOTAL.load(Ordering::Relaxed),
+        calls_failed: CALLS_FAILED.load(Ordering::Relaxed),
+        fee_nanotons_total: FEE_NANOTONS_TOTAL.load(Ordering::Relaxed),
+        wait_seconds: WAIT_MILLIS.load(Ordering::Relaxed) as f64 / 1000.0,
+    }
+}
+
+/// Writes metrics snapshot to the output file if metrics are enabled and the file is set.
+pub fn flush() -> Result<(), String> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    if let Some(path) = OUTPUT_FILE.lock().unwrap().as_ref() {
+        std::fs::write(path, snapshot().to_prometheus())
+            .map_err(|e| format!("failed to write metrics to file {}: {}", path, e))?;
+    }
+    Ok(())
+}
@@ -322,6 +322,7 @@ pub async fn send_with_body(
         &params,
         Some(keys.to_owned()),
         false,
+        call::CallOptions::default(),
     ).await
 }
 
@@ -383,6 +384,7 @@ async fn multisig_deploy_command(matches: &ArgMatches<'_>, config: &Config) -> R
             &params,
             None,
             false,
+            call::CallOptions::default(),
         ).await?;
     }
 
@@ -17,12 +17,13 @@ use ton_block::{Account, Deserializable, Message, Serializable};
 use ton_client::abi::{FunctionHeader};
 use ton_client::tvm::{ExecutionOptions, ParamsOfRunGet, ParamsOfRunTvm, run_get, run_tvm};
 use crate::config::{Config, FullConfig};
-use crate::call::{print_json_result};
+use crate::call::{display_signed_outputs, normalize_output_addresses, print_json_result, resolve_method};
 use crate::debug::{execute_debug, DebugLogger};
 use crate::helpers::{create_client, now, now_ms, SDK_EXECUTION_ERROR_CODE, TonClient,
                      contract_data_from_matches_or_config_alias, abi_from_matches_or_config,
                      AccountSource, create_client_local, create_client_verbose, load_abi,
-                     load_account, load_params, unpack_alternative_params, get_blockchain_config};
+                     load_account, load_params, unpack_alternative_params, get_blockchain_config,
+                     load_ton_abi};
 use crate::message::prepare_message;
 
 pub async fn run_command(matches: &ArgMatches<'_>, full_config: &FullConfig, is_alternative: bool) -> Result<(), String> {
@@ -92,6 +93,7 @@ async fn run(
     } else {
         matches.value_of("METHOD").unwrap()
     };
+    let method = &resolve_method(&abi_path, method, config).await?;
     let bc_config = matches.value_of("BCCONFIG");
 
     if !config.is_json {
@@ -202,6 +204,15 @@ async fn run(
         let res = result.decoded.and_then(|d| d.output);
         match res {
             Some(data) => {
+                let abi = load_ton_abi(&abi_path, config).await?;
+                let data = match abi.function(method) {
+                    Ok(function) => normalize_output_addresses(
+                        display_signed_outputs(data, &function.outputs),
+                        &function.outputs,
+                        config,
+                    ),
+                    Err(_) => data,
+                };
                 print_json_result(data, config)?;
             },
             None => {
@@ -14,8 +14,20 @@ use crate::config::Config;
 use crate::{call, message};
 use crate::helpers::{create_client_local, decode_msg_body};
 use crate::multisig::{encode_transfer_body, MSIG_ABI, TRANSFER_WITH_COMMENT};
+use serde::Deserialize;
 use serde_json::json;
 
+#[derive(Deserialize)]
+struct MsigTransaction {
+	id: String,
+	payload: String,
+}
+
+#[derive(Deserialize)]
+struct MsigTransactions {
+	transactions: Vec<MsigTransaction>,
+}
+
 pub async fn create_proposal(
 	config: &Config,
 	addr: &str,
@@ -50,6 +62,8 @@ pub async fn create_proposal(
 			false,
 			None,
 			None,
+			None,
+			None,
 		).await
 	} else {
 
@@ -61,6 +75,7 @@ pub async fn create_proposal(
 			&params,
 			keys,
 			false,
+			call::CallOptions::default(),
 		).await
 	}
 }
@@ -92,6 +107,8 @@ pub async fn vote(
 			false,
 			None,
 			None,
+			None,
+			None,
 		).await
 	} else {
 		call::call_contract(
@@ -102,6 +119,7 @@ pub async fn vote(
 			&params,
 			keys,
 			false,
+			call::CallOptions::default(),
 		).await
 	}
 }
@@ -113,26 +131,18 @@ pub async fn decode_proposal(
 ) -> Result<(), String> {
 
 	// change to run
-	let result = call::call_contract_with_result(
+	let result: MsigTransactions = call::call_and_decode(
 		config,
 		addr,
 		MSIG_ABI,
 		"getTransactions",
 		"{}",
 		None,
-		false,
 	).await?;
 
-	let txns = result["transactions"].as_array()
-		.ok_or(r#"failed to decode result: "transactions" array not found"#.to_string())?;
-
-	for txn in txns {
-		let txn_id = txn["id"].as_str()
-			.ok_or(r#"failed to parse transaction in list: "id" not found"#.to_string())?;
-
-		if txn_id == proposal_id {
-			let body = txn["payload"].as_str()
-				.ok_or(r#"failed to parse transaction in list: "payload" not found"#.to_string())?;
+	for txn in result.transactions {
+		if txn.id == proposal_id {
+			let body = &txn.payload;
 			let ton = create_client_local()?;
 			let result = decode_msg_body(
 				ton.clone(),